@@ -1,15 +1,47 @@
 use bevy_ecs::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug, Deserialize, Serialize)]
+// Read and written as a plain name, so data can use ids the code has never heard of
+#[derive(Hash, PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
 pub enum AppearanceKind {
     MaleBrownHairBlueBody,
     Golem,
     FireBolt,
+    // Described only by a sprite sheet in the data, such as a newly drawn monster
+    Custom(String),
+}
+
+impl AppearanceKind {
+    pub fn name(&self) -> &str {
+        match self {
+            AppearanceKind::MaleBrownHairBlueBody => "MaleBrownHairBlueBody",
+            AppearanceKind::Golem => "Golem",
+            AppearanceKind::FireBolt => "FireBolt",
+            AppearanceKind::Custom(name) => name,
+        }
+    }
+}
+
+impl From<String> for AppearanceKind {
+    fn from(name: String) -> Self {
+        match name.as_str() {
+            "MaleBrownHairBlueBody" => AppearanceKind::MaleBrownHairBlueBody,
+            "Golem" => AppearanceKind::Golem,
+            "FireBolt" => AppearanceKind::FireBolt,
+            _ => AppearanceKind::Custom(name),
+        }
+    }
+}
+
+impl From<AppearanceKind> for String {
+    fn from(kind: AppearanceKind) -> Self {
+        kind.name().to_string()
+    }
 }
 
 #[allow(dead_code)]
-#[derive(PartialEq, Eq, Clone, Copy, Debug, Deserialize, Serialize)]
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug, Deserialize, Serialize)]
 pub enum AnimationState {
     AttackOne,
     AttackTwo,
@@ -44,3 +76,16 @@ impl Appearance {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appearance_names() {
+        assert_eq!(AppearanceKind::Golem, AppearanceKind::from("Golem".to_string()));
+        assert_eq!(AppearanceKind::Custom("Bird".to_string()), AppearanceKind::from("Bird".to_string()));
+        assert_eq!("Bird", AppearanceKind::Custom("Bird".to_string()).name());
+        assert_eq!("FireBolt", String::from(AppearanceKind::FireBolt));
+    }
+}
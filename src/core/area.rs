@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+use super::{extend_line_along_path, Map, Point, SizedPoint};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum AreaShape {
+    Single,
+    // Radius around the target
+    Circle(u32),
+    // Length from the invoker towards the target
+    Cone(u32),
+    // Length from the invoker through the target
+    Line(u32),
+    // Arm length around the target
+    Cross(u32),
+}
+
+pub fn affected_area(invoker: SizedPoint, target: Point, shape: AreaShape) -> Vec<Point> {
+    let area = match shape {
+        AreaShape::Single => vec![target],
+        AreaShape::Circle(radius) => circle(target, radius),
+        AreaShape::Cone(length) => cone(invoker, target, length),
+        AreaShape::Line(length) => line(invoker, target, length),
+        AreaShape::Cross(length) => cross(target, length),
+    };
+    area.into_iter().filter(|p| p.in_bounds()).collect()
+}
+
+fn all_map_points() -> impl Iterator<Item = Point> {
+    (0..Map::MAX_TILES as u32).flat_map(|y| (0..Map::MAX_TILES as u32).map(move |x| Point::new(x, y)))
+}
+
+fn delta(from: Point, to: Point) -> (i32, i32) {
+    (to.x as i32 - from.x as i32, to.y as i32 - from.y as i32)
+}
+
+fn circle(center: Point, radius: u32) -> Vec<Point> {
+    // The extra radius rounds off the corners so small circles are not diamonds
+    let radius = radius as i32;
+    all_map_points()
+        .filter(|p| {
+            let (x, y) = delta(center, *p);
+            x * x + y * y <= radius * radius + radius
+        })
+        .collect()
+}
+
+fn cone(invoker: SizedPoint, target: Point, length: u32) -> Vec<Point> {
+    let origin = invoker.nearest_point_to(SizedPoint::from(target));
+    let (dir_x, dir_y) = delta(origin, target);
+    if dir_x == 0 && dir_y == 0 {
+        return vec![];
+    }
+    let dir_length = ((dir_x * dir_x + dir_y * dir_y) as f32).sqrt();
+
+    all_map_points()
+        .filter(|p| {
+            if invoker.contains_point(p) {
+                return false;
+            }
+            let (x, y) = delta(origin, *p);
+            if std::cmp::max(x.abs(), y.abs()) as u32 > length {
+                return false;
+            }
+            // Everything within 45 degrees of the line to the target
+            let cos = (x * dir_x + y * dir_y) as f32 / (((x * x + y * y) as f32).sqrt() * dir_length);
+            cos >= std::f32::consts::FRAC_1_SQRT_2 - 0.001
+        })
+        .collect()
+}
+
+fn line(invoker: SizedPoint, target: Point, length: u32) -> Vec<Point> {
+    match invoker.line_to(target) {
+        // The path starts on the invoker, which the line should not hit
+        Some(path) => extend_line_along_path(&path, length + 1).into_iter().skip(1).collect(),
+        None => vec![],
+    }
+}
+
+fn cross(center: Point, length: u32) -> Vec<Point> {
+    let length = length as i32;
+    all_map_points()
+        .filter(|p| {
+            let (x, y) = delta(center, *p);
+            (x == 0 && y.abs() <= length) || (y == 0 && x.abs() <= length)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single() {
+        let area = affected_area(SizedPoint::new(2, 2), Point::new(5, 5), AreaShape::Single);
+        assert_eq!(vec![Point::new(5, 5)], area);
+    }
+
+    #[test]
+    fn circle_rounds_corners() {
+        let area = affected_area(SizedPoint::new(2, 2), Point::new(5, 5), AreaShape::Circle(1));
+        assert_eq!(9, area.len());
+
+        let area = affected_area(SizedPoint::new(2, 2), Point::new(5, 5), AreaShape::Circle(2));
+        assert_eq!(21, area.len());
+        assert!(area.contains(&Point::new(7, 6)));
+        assert!(!area.contains(&Point::new(7, 7)));
+    }
+
+    #[test]
+    fn circle_at_map_edge() {
+        let area = affected_area(SizedPoint::new(2, 2), Point::new(0, 0), AreaShape::Circle(1));
+        assert_eq!(4, area.len());
+    }
+
+    #[test]
+    fn cone_east() {
+        // . . * .
+        // . * * .
+        // I * * .
+        // . * * .
+        // . . * .
+        let area = affected_area(SizedPoint::new(5, 5), Point::new(6, 5), AreaShape::Cone(2));
+        assert_eq!(8, area.len());
+        assert!(area.contains(&Point::new(6, 4)));
+        assert!(area.contains(&Point::new(7, 3)));
+        assert!(area.contains(&Point::new(7, 7)));
+        assert!(!area.contains(&Point::new(5, 4)));
+        assert!(!area.contains(&Point::new(8, 5)));
+    }
+
+    #[test]
+    fn cone_on_self() {
+        let area = affected_area(SizedPoint::new(5, 5), Point::new(5, 5), AreaShape::Cone(2));
+        assert!(area.is_empty());
+    }
+
+    #[test]
+    fn line_pierces_past_target() {
+        let area = affected_area(SizedPoint::new(2, 2), Point::new(2, 4), AreaShape::Line(4));
+        assert_eq!(vec![Point::new(2, 3), Point::new(2, 4), Point::new(2, 5), Point::new(2, 6)], area);
+    }
+
+    #[test]
+    fn line_from_sized() {
+        let area = affected_area(SizedPoint::new_sized(2, 2, 2, 2), Point::new(6, 3), AreaShape::Line(3));
+        assert_eq!(vec![Point::new(4, 3), Point::new(5, 3), Point::new(6, 3)], area);
+    }
+
+    #[test]
+    fn cross() {
+        let area = affected_area(SizedPoint::new(2, 2), Point::new(6, 6), AreaShape::Cross(2));
+        assert_eq!(9, area.len());
+        assert!(area.contains(&Point::new(6, 4)));
+        assert!(area.contains(&Point::new(8, 6)));
+        assert!(!area.contains(&Point::new(7, 7)));
+    }
+}
@@ -0,0 +1,66 @@
+use bevy_ecs::prelude::*;
+
+use super::{NewMessageEvent, SizedPoint, StatusEffectKind};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CombatEventKind {
+    Damage { dealt: u32, absorbed: u32 },
+    Heal(u32),
+    Miss,
+    StatusApplied(StatusEffectKind),
+}
+
+// Something that happened to a character in a fight, which feeds both the log and the floating text over the map
+#[derive(Clone, Debug)]
+pub struct CombatEvent {
+    pub target: Option<Entity>,
+    // Where it happened, as the target may have moved or died by the time it is shown
+    pub position: Option<SizedPoint>,
+    pub kind: CombatEventKind,
+}
+
+impl CombatEvent {
+    pub fn new(target: Option<Entity>, position: Option<SizedPoint>, kind: CombatEventKind) -> Self {
+        CombatEvent { target, position, kind }
+    }
+
+    pub fn message(&self) -> String {
+        match self.kind {
+            CombatEventKind::Damage { dealt, absorbed } if absorbed > 0 => format!("{} damage ({} absorbed).", dealt, absorbed),
+            CombatEventKind::Damage { dealt, .. } => format!("{} damage.", dealt),
+            CombatEventKind::Heal(amount) => format!("{} healed.", amount),
+            CombatEventKind::Miss => "Miss.".to_string(),
+            CombatEventKind::StatusApplied(kind) => format!("{} applied.", kind.name()),
+        }
+    }
+}
+
+pub fn send_combat_event(world: &mut World, event: CombatEvent) {
+    world.send_event(NewMessageEvent::new(&event.message()));
+    world.send_event(event);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{apply_damage, Health, Position};
+
+    use super::*;
+
+    #[test]
+    fn damage_reports_where_it_landed() {
+        let mut world = World::new();
+        world.insert_resource(Events::<CombatEvent>::default());
+        let entity = world.spawn().insert(Health::new(10)).insert(Position::new_sized(3, 3, 2, 2)).id();
+        apply_damage(&mut world, entity, 2);
+        apply_damage(&mut world, entity, 3);
+
+        let events = world.get_resource::<Events<CombatEvent>>().unwrap();
+        let mut reader = events.get_reader();
+        let sent: Vec<&CombatEvent> = reader.iter(events).collect();
+        assert_eq!(2, sent.len());
+        assert_eq!(Some(entity), sent[0].target);
+        assert_eq!(Some(SizedPoint::new_sized(3, 3, 2, 2)), sent[0].position);
+        assert_eq!(CombatEventKind::Damage { dealt: 3, absorbed: 0 }, sent[1].kind);
+        assert_eq!("3 damage.", sent[1].message());
+    }
+}
@@ -0,0 +1,58 @@
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{find_position, Direction, SizedPoint};
+
+#[derive(Component, Debug, Deserialize, Serialize)]
+pub struct Facing {
+    pub direction: Direction,
+}
+
+impl Facing {
+    pub fn new(direction: Direction) -> Self {
+        Facing { direction }
+    }
+}
+
+pub fn find_facing(world: &World, entity: Entity) -> Option<Direction> {
+    world.get::<Facing>(entity).map(|f| f.direction)
+}
+
+// Turns to look at the target, keeping the current facing when it is underfoot
+pub fn face_towards(world: &mut World, entity: Entity, target: SizedPoint) {
+    let position = match find_position(world, entity) {
+        Some(position) => position,
+        None => return,
+    };
+    let start = position.nearest_point_to(target);
+    let end = target.nearest_point_to(SizedPoint::from(start));
+    let direction = Direction::from_two_points(&SizedPoint::from(start), &SizedPoint::from(end));
+    if direction != Direction::None {
+        if let Some(mut facing) = world.get_mut::<Facing>(entity) {
+            facing.direction = direction;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Point, Position};
+
+    use super::*;
+
+    #[test]
+    fn faces_target() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Position::new_sized(2, 2, 2, 2)).insert(Facing::new(Direction::South)).id();
+
+        face_towards(&mut world, entity, SizedPoint::new(6, 3));
+        assert_eq!(Some(Direction::East), find_facing(&world, entity));
+
+        face_towards(&mut world, entity, SizedPoint::new(0, 0));
+        assert_eq!(Some(Direction::NorthWest), find_facing(&world, entity));
+
+        // Targeting our own tiles leaves us facing where we were
+        face_towards(&mut world, entity, SizedPoint::from(Point::new(3, 3)));
+        assert_eq!(Some(Direction::NorthWest), find_facing(&world, entity));
+    }
+}
@@ -1,24 +1,288 @@
 use bevy_ecs::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use super::Point;
+use super::{add_status_effect, apply_damage, apply_healing, is_allied, Character, Point, Position, StatusEffectKind};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum FieldColor {
     Gray,
+    Red,
+    Green,
+    Blue,
+    Black,
+    White,
+    Yellow,
+}
+
+// Kinds other than Inert interact with each other each turn, see field_rules
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum FieldKind {
+    Inert,
+    Fire,
+    Ice,
+    Water,
+    Lightning,
+    Electrified,
+    Oil,
+    Poison,
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum FieldEffect {
+    None,
+    Damage(u32),
+    Slow,
+    BlockVision,
+    Heal(u32),
 }
 
 #[derive(Component, Debug, Deserialize, Serialize)]
 pub struct Fields {
+    pub kind: FieldKind,
     pub color: FieldColor,
     pub positions: Vec<Point>,
+    pub effect: FieldEffect,
+    // None lasts until removed
+    pub duration: Option<u32>,
+    // Who made it, whose allies it spares or heals. Entities do not outlive the world, so it is never saved.
+    #[serde(skip)]
+    pub owner: Option<Entity>,
 }
 
 impl Fields {
     pub fn new(color: FieldColor, positions: &[Point]) -> Self {
         Fields {
+            kind: FieldKind::Inert,
             color,
             positions: Vec::from(positions),
+            effect: FieldEffect::None,
+            duration: None,
+            owner: None,
+        }
+    }
+
+    pub fn for_kind(kind: FieldKind, positions: &[Point]) -> Self {
+        let (color, effect, duration) = match kind {
+            FieldKind::Inert => (FieldColor::Gray, FieldEffect::None, None),
+            FieldKind::Fire => (FieldColor::Red, FieldEffect::Damage(2), Some(5)),
+            FieldKind::Ice => (FieldColor::White, FieldEffect::Slow, None),
+            FieldKind::Water => (FieldColor::Blue, FieldEffect::Slow, None),
+            FieldKind::Lightning => (FieldColor::Yellow, FieldEffect::Damage(3), Some(1)),
+            FieldKind::Electrified => (FieldColor::Yellow, FieldEffect::Damage(2), Some(3)),
+            FieldKind::Oil => (FieldColor::Black, FieldEffect::Slow, None),
+            FieldKind::Poison => (FieldColor::Green, FieldEffect::Damage(1), Some(10)),
+        };
+        Fields {
+            kind,
+            color,
+            positions: Vec::from(positions),
+            effect,
+            duration,
+            owner: None,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_effect(mut self, effect: FieldEffect) -> Self {
+        self.effect = effect;
+        self
+    }
+
+    pub fn with_duration(mut self, duration: u32) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    pub fn with_owner(mut self, owner: Entity) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+}
+
+pub fn blocks_vision(world: &mut World, path: &[Point]) -> bool {
+    let mut query = world.query::<&Fields>();
+    query
+        .iter(world)
+        .filter(|f| f.effect == FieldEffect::BlockVision)
+        .any(|f| path.iter().any(|p| f.positions.contains(p)))
+}
+
+// Fields left by someone heal only their allies and harm everyone else, while
+// those without a living owner affect everyone alike
+fn field_affects(world: &World, owner: Option<Entity>, effect: FieldEffect, character: Entity) -> bool {
+    match owner.filter(|o| world.get_entity(*o).is_some()) {
+        Some(owner) => match effect {
+            FieldEffect::Heal(_) => is_allied(world, owner, character),
+            _ => !is_allied(world, owner, character),
+        },
+        None => true,
+    }
+}
+
+// Every character standing in a field at the end of a turn, including those who just
+// entered it, feels its effect once per field no matter how many tiles they cover
+pub fn apply_fields(world: &mut World) {
+    let fields: Vec<(FieldEffect, Option<Entity>, Vec<Point>)> =
+        world.query::<&Fields>().iter(world).map(|f| (f.effect, f.owner, f.positions.clone())).collect();
+
+    let mut affected = vec![];
+    let mut query = world.query_filtered::<(Entity, &Position), With<Character>>();
+    for (entity, position) in query.iter(world) {
+        for (effect, owner, positions) in &fields {
+            if positions.iter().any(|p| position.position.contains_point(p)) && field_affects(world, *owner, *effect, entity) {
+                affected.push((entity, *effect));
+            }
+        }
+    }
+
+    for (entity, effect) in affected {
+        match effect {
+            FieldEffect::None | FieldEffect::BlockVision => {}
+            FieldEffect::Damage(amount) => {
+                apply_damage(world, entity, amount);
+            }
+            FieldEffect::Slow => add_status_effect(world, entity, StatusEffectKind::Slowed, Some(2), 1),
+            FieldEffect::Heal(amount) => {
+                apply_healing(world, entity, amount);
+            }
         }
     }
 }
+
+pub fn tick_fields(world: &mut World) {
+    let mut expired = vec![];
+    let mut query = world.query::<(Entity, &mut Fields)>();
+    for (entity, mut field) in query.iter_mut(world) {
+        if let Some(duration) = &mut field.duration {
+            *duration = duration.saturating_sub(1);
+            if *duration == 0 {
+                expired.push(entity);
+            }
+        }
+    }
+    for entity in expired {
+        world.despawn(entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{end_turn, Health, StatusEffects, Team};
+
+    use super::*;
+
+    #[test]
+    fn damage_each_turn() {
+        let mut world = World::new();
+        let character = world
+            .spawn()
+            .insert(Character)
+            .insert(Position::new_sized(2, 2, 2, 2))
+            .insert(Health::new(10))
+            .id();
+        world
+            .spawn()
+            .insert(Fields::new(FieldColor::Red, &[Point::new(3, 3), Point::new(3, 2)]).with_effect(FieldEffect::Damage(2)));
+
+        end_turn(&mut world);
+        assert_eq!(8, world.get::<Health>(character).unwrap().current);
+        end_turn(&mut world);
+        assert_eq!(6, world.get::<Health>(character).unwrap().current);
+    }
+
+    #[test]
+    fn heal_outside_field() {
+        let mut world = World::new();
+        let character = world.spawn().insert(Character).insert(Position::new(2, 2)).insert(Health::new(10)).id();
+        world
+            .spawn()
+            .insert(Fields::new(FieldColor::Green, &[Point::new(3, 3)]).with_effect(FieldEffect::Heal(2)));
+        apply_damage(&mut world, character, 5);
+
+        end_turn(&mut world);
+        assert_eq!(5, world.get::<Health>(character).unwrap().current);
+
+        world.get_mut::<Position>(character).unwrap().position = Position::new(3, 3).position;
+        end_turn(&mut world);
+        assert_eq!(7, world.get::<Health>(character).unwrap().current);
+    }
+
+    #[test]
+    fn slow_lasts_while_standing() {
+        let mut world = World::new();
+        let character = world.spawn().insert(Character).insert(Position::new(2, 2)).id();
+        world
+            .spawn()
+            .insert(Fields::new(FieldColor::Blue, &[Point::new(2, 2)]).with_effect(FieldEffect::Slow));
+
+        end_turn(&mut world);
+        assert!(world.get::<StatusEffects>(character).unwrap().has(StatusEffectKind::Slowed));
+        end_turn(&mut world);
+        assert!(world.get::<StatusEffects>(character).unwrap().has(StatusEffectKind::Slowed));
+
+        world.get_mut::<Position>(character).unwrap().position = Position::new(4, 4).position;
+        end_turn(&mut world);
+        assert!(!world.get::<StatusEffects>(character).unwrap().has(StatusEffectKind::Slowed));
+    }
+
+    #[test]
+    fn owned_fields_respect_teams() {
+        let mut world = World::new();
+        let owner = world.spawn().insert(Character).insert(Team::Player).insert(Position::new(0, 0)).id();
+        let ally = world
+            .spawn()
+            .insert(Character)
+            .insert(Team::Player)
+            .insert(Position::new(2, 2))
+            .insert(Health::new(10))
+            .id();
+        let enemy = world
+            .spawn()
+            .insert(Character)
+            .insert(Team::Enemy)
+            .insert(Position::new(3, 2))
+            .insert(Health::new(10))
+            .id();
+        let area = [Point::new(2, 2), Point::new(3, 2)];
+        world
+            .spawn()
+            .insert(Fields::new(FieldColor::Red, &area).with_effect(FieldEffect::Damage(3)).with_owner(owner));
+        world
+            .spawn()
+            .insert(Fields::new(FieldColor::Green, &area).with_effect(FieldEffect::Heal(1)).with_owner(owner));
+
+        end_turn(&mut world);
+        assert_eq!(10, world.get::<Health>(ally).unwrap().current);
+        assert_eq!(7, world.get::<Health>(enemy).unwrap().current);
+
+        // Once the owner is gone the field hurts everyone
+        world.despawn(owner);
+        end_turn(&mut world);
+        assert_eq!(8, world.get::<Health>(ally).unwrap().current);
+        assert_eq!(5, world.get::<Health>(enemy).unwrap().current);
+    }
+
+    #[test]
+    fn fields_expire() {
+        let mut world = World::new();
+        let field = world.spawn().insert(Fields::new(FieldColor::Red, &[Point::new(2, 2)]).with_duration(2)).id();
+
+        end_turn(&mut world);
+        assert!(world.get_entity(field).is_some());
+        end_turn(&mut world);
+        assert!(world.get_entity(field).is_none());
+    }
+
+    #[test]
+    fn block_vision() {
+        let mut world = World::new();
+        world
+            .spawn()
+            .insert(Fields::new(FieldColor::Black, &[Point::new(2, 2)]).with_effect(FieldEffect::BlockVision));
+        world.spawn().insert(Fields::new(FieldColor::Red, &[Point::new(3, 3)]));
+
+        assert!(blocks_vision(&mut world, &[Point::new(1, 1), Point::new(2, 2)]));
+        assert!(!blocks_vision(&mut world, &[Point::new(3, 3)]));
+    }
+}
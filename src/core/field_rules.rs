@@ -0,0 +1,229 @@
+use bevy_ecs::prelude::*;
+
+use super::{FieldKind, Fields, Point};
+
+pub struct FieldRule {
+    pub name: &'static str,
+    pub trigger: FieldKind,
+    pub affected: FieldKind,
+    // None removes the affected tile
+    pub result: Option<FieldKind>,
+}
+
+// Each turn, any tile of the affected kind on or next to a tile of the trigger kind
+// becomes the result. Earlier rules win when more than one applies to a tile.
+pub const FIELD_RULES: &[FieldRule] = &[
+    FieldRule {
+        name: "Water extinguishes fire",
+        trigger: FieldKind::Water,
+        affected: FieldKind::Fire,
+        result: None,
+    },
+    FieldRule {
+        name: "Fire spreads through oil",
+        trigger: FieldKind::Fire,
+        affected: FieldKind::Oil,
+        result: Some(FieldKind::Fire),
+    },
+    FieldRule {
+        name: "Fire ignites poison",
+        trigger: FieldKind::Fire,
+        affected: FieldKind::Poison,
+        result: Some(FieldKind::Fire),
+    },
+    FieldRule {
+        name: "Fire melts ice",
+        trigger: FieldKind::Fire,
+        affected: FieldKind::Ice,
+        result: Some(FieldKind::Water),
+    },
+    FieldRule {
+        name: "Lightning electrifies water",
+        trigger: FieldKind::Lightning,
+        affected: FieldKind::Water,
+        result: Some(FieldKind::Electrified),
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FiredFieldRule {
+    pub rule: usize,
+    pub position: Point,
+}
+
+// The rules that fired on the last turn, for the debug overlay
+pub struct FieldRuleLog {
+    pub fired: Vec<FiredFieldRule>,
+}
+
+impl FieldRuleLog {
+    pub fn new() -> Self {
+        FieldRuleLog { fired: vec![] }
+    }
+}
+
+struct FieldTile {
+    position: Point,
+    kind: FieldKind,
+    entity: Entity,
+    owner: Option<Entity>,
+}
+
+fn is_adjacent(first: Point, second: Point) -> bool {
+    (first.x as i32 - second.x as i32).abs() <= 1 && (first.y as i32 - second.y as i32).abs() <= 1
+}
+
+pub fn update_fields(world: &mut World) -> Vec<FiredFieldRule> {
+    // Work off a snapshot in a stable order so changes this turn never cascade
+    // and the outcome does not depend on entity spawn order
+    let mut tiles = vec![];
+    for (entity, field) in world.query::<(Entity, &Fields)>().iter(world) {
+        for position in &field.positions {
+            tiles.push(FieldTile {
+                position: *position,
+                kind: field.kind,
+                entity,
+                owner: field.owner,
+            });
+        }
+    }
+    tiles.sort_by_key(|t| (t.position.y, t.position.x, t.kind as u32, t.entity.id()));
+
+    let mut fired = vec![];
+    let mut changes = vec![];
+    for tile in &tiles {
+        for (index, rule) in FIELD_RULES.iter().enumerate() {
+            if rule.affected != tile.kind {
+                continue;
+            }
+            if let Some(trigger) = tiles.iter().find(|t| t.kind == rule.trigger && is_adjacent(t.position, tile.position)) {
+                fired.push(FiredFieldRule {
+                    rule: index,
+                    position: tile.position,
+                });
+                changes.push((tile.position, tile.entity, rule.result, trigger.owner));
+                break;
+            }
+        }
+    }
+
+    let mut created: Vec<(FieldKind, Option<Entity>, Vec<Point>)> = vec![];
+    for (position, entity, result, owner) in changes {
+        if let Some(mut field) = world.get_mut::<Fields>(entity) {
+            field.positions.retain(|p| *p != position);
+        }
+        if let Some(kind) = result {
+            match created.iter_mut().find(|(k, o, _)| *k == kind && *o == owner) {
+                Some((_, _, positions)) => positions.push(position),
+                None => created.push((kind, owner, vec![position])),
+            }
+        }
+    }
+
+    for (kind, owner, positions) in created {
+        let mut field = Fields::for_kind(kind, &positions);
+        field.owner = owner;
+        world.spawn().insert(field);
+    }
+
+    let empty: Vec<Entity> = world
+        .query::<(Entity, &Fields)>()
+        .iter(world)
+        .filter(|(_, f)| f.positions.is_empty())
+        .map(|(e, _)| e)
+        .collect();
+    for entity in empty {
+        world.despawn(entity);
+    }
+
+    fired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind_at(world: &mut World, position: Point) -> Vec<FieldKind> {
+        let mut kinds: Vec<FieldKind> = world
+            .query::<&Fields>()
+            .iter(world)
+            .filter(|f| f.positions.contains(&position))
+            .map(|f| f.kind)
+            .collect();
+        kinds.sort_by_key(|k| *k as u32);
+        kinds
+    }
+
+    #[test]
+    fn fire_spreads_one_tile_per_turn() {
+        let mut world = World::new();
+        world.spawn().insert(Fields::for_kind(FieldKind::Fire, &[Point::new(2, 2)]));
+        world
+            .spawn()
+            .insert(Fields::for_kind(FieldKind::Oil, &[Point::new(3, 2), Point::new(4, 2), Point::new(5, 2)]));
+
+        update_fields(&mut world);
+        assert_eq!(vec![FieldKind::Fire], kind_at(&mut world, Point::new(3, 2)));
+        assert_eq!(vec![FieldKind::Oil], kind_at(&mut world, Point::new(4, 2)));
+
+        update_fields(&mut world);
+        assert_eq!(vec![FieldKind::Fire], kind_at(&mut world, Point::new(4, 2)));
+        assert_eq!(vec![FieldKind::Oil], kind_at(&mut world, Point::new(5, 2)));
+    }
+
+    #[test]
+    fn water_extinguishes_fire() {
+        let mut world = World::new();
+        world.spawn().insert(Fields::for_kind(FieldKind::Fire, &[Point::new(2, 2), Point::new(8, 8)]));
+        world.spawn().insert(Fields::for_kind(FieldKind::Water, &[Point::new(3, 3)]));
+
+        let fired = update_fields(&mut world);
+        assert_eq!(1, fired.len());
+        assert_eq!(0, fired[0].rule);
+        assert!(kind_at(&mut world, Point::new(2, 2)).is_empty());
+        assert_eq!(vec![FieldKind::Fire], kind_at(&mut world, Point::new(8, 8)));
+        assert_eq!(vec![FieldKind::Water], kind_at(&mut world, Point::new(3, 3)));
+    }
+
+    #[test]
+    fn ice_melts_then_electrifies() {
+        let mut world = World::new();
+        world.spawn().insert(Fields::for_kind(FieldKind::Fire, &[Point::new(2, 2)]));
+        world.spawn().insert(Fields::for_kind(FieldKind::Ice, &[Point::new(2, 3)]));
+        world.spawn().insert(Fields::for_kind(FieldKind::Lightning, &[Point::new(2, 4)]));
+
+        update_fields(&mut world);
+        assert_eq!(vec![FieldKind::Water], kind_at(&mut world, Point::new(2, 3)));
+
+        // The new water puts out the fire while the lightning charges it
+        let fired = update_fields(&mut world);
+        assert_eq!(2, fired.len());
+        assert!(kind_at(&mut world, Point::new(2, 2)).is_empty());
+        assert_eq!(vec![FieldKind::Electrified], kind_at(&mut world, Point::new(2, 3)));
+    }
+
+    #[test]
+    fn empty_fields_are_removed() {
+        let mut world = World::new();
+        world.spawn().insert(Fields::for_kind(FieldKind::Water, &[Point::new(2, 2)]));
+        let fire = world.spawn().insert(Fields::for_kind(FieldKind::Fire, &[Point::new(2, 3)])).id();
+
+        update_fields(&mut world);
+        assert!(world.get_entity(fire).is_none());
+    }
+
+    #[test]
+    fn independent_of_spawn_order() {
+        let mut first = World::new();
+        first.spawn().insert(Fields::for_kind(FieldKind::Fire, &[Point::new(2, 2)]));
+        first.spawn().insert(Fields::for_kind(FieldKind::Oil, &[Point::new(3, 3), Point::new(1, 1)]));
+        first.spawn().insert(Fields::for_kind(FieldKind::Ice, &[Point::new(2, 1)]));
+
+        let mut second = World::new();
+        second.spawn().insert(Fields::for_kind(FieldKind::Ice, &[Point::new(2, 1)]));
+        second.spawn().insert(Fields::for_kind(FieldKind::Oil, &[Point::new(1, 1), Point::new(3, 3)]));
+        second.spawn().insert(Fields::for_kind(FieldKind::Fire, &[Point::new(2, 2)]));
+
+        assert_eq!(update_fields(&mut first), update_fields(&mut second));
+    }
+}
@@ -0,0 +1,173 @@
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    find_facing, find_position, relation_between, send_combat_event, AnimationState, Appearance, Character, CombatEvent, CombatEventKind, Direction,
+    NewMessageEvent, Player, Relation, StatusEffectKind, StatusEffects,
+};
+use crate::ui::{EffectKind, SpawnEffectEvent, SpriteAnimateActionEvent};
+
+#[derive(Component, Debug, Deserialize, Serialize)]
+pub struct Health {
+    pub current: u32,
+    pub max: u32,
+}
+
+impl Health {
+    pub fn new(max: u32) -> Self {
+        Health { current: max, max }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current == 0
+    }
+
+    pub fn life_percentage(&self) -> f32 {
+        self.current as f32 / self.max as f32
+    }
+}
+
+pub fn absorb_percentage(world: &World, entity: Entity) -> f32 {
+    let shield = world.get::<StatusEffects>(entity).map(|e| e.stacks(StatusEffectKind::Shielded)).unwrap_or(0);
+    match world.get::<Health>(entity) {
+        Some(health) if shield > 0 => f32::min(shield as f32 / health.max as f32, 1.0),
+        _ => 0.0,
+    }
+}
+
+// Returns the damage that made it through any shield
+pub fn apply_damage(world: &mut World, target: Entity, amount: u32) -> u32 {
+    let absorbed = world.get_mut::<StatusEffects>(target).map(|mut e| e.absorb(amount)).unwrap_or(0);
+    let dealt = match world.get_mut::<Health>(target) {
+        Some(mut health) => {
+            let dealt = std::cmp::min(amount - absorbed, health.current);
+            health.current -= dealt;
+            dealt
+        }
+        None => 0,
+    };
+
+    let position = find_position(world, target);
+    send_combat_event(world, CombatEvent::new(Some(target), position, CombatEventKind::Damage { dealt, absorbed }));
+    if dealt > 0 || absorbed > 0 {
+        world.send_event(SpriteAnimateActionEvent::new(target, AnimationState::Hit));
+    }
+    dealt
+}
+
+pub fn apply_healing(world: &mut World, target: Entity, amount: u32) -> u32 {
+    let healed = match world.get_mut::<Health>(target) {
+        Some(mut health) => {
+            let healed = std::cmp::min(amount, health.max - health.current);
+            health.current += healed;
+            healed
+        }
+        None => 0,
+    };
+    if healed > 0 {
+        let position = find_position(world, target);
+        send_combat_event(world, CombatEvent::new(Some(target), position, CombatEventKind::Heal(healed)));
+    }
+    healed
+}
+
+// Characters out of health leave the field, fading out where they fell. The player
+// stays put as there is nothing yet to go to once they have fallen.
+pub fn remove_dead(world: &mut World) {
+    let mut query = world.query_filtered::<(Entity, &Health), (With<Character>, Without<Player>)>();
+    let dead: Vec<Entity> = query.iter(world).filter(|(_, health)| health.is_dead()).map(|(entity, _)| entity).collect();
+    let player = world.query_filtered::<Entity, With<Player>>().iter(world).next();
+    for entity in dead {
+        // Summons and other allies fall too, so name them from the player's side
+        let message = match player.map(|player| relation_between(world, player, entity)) {
+            Some(Relation::Hostile) => "A foe falls.",
+            Some(Relation::Allied) => "An ally falls.",
+            _ => "A bystander falls.",
+        };
+        let appearance = world.get::<Appearance>(entity).map(|a| a.kind.clone());
+        let facing = find_facing(world, entity).unwrap_or(Direction::None);
+        if let (Some(appearance), Some(position)) = (appearance, find_position(world, entity)) {
+            world.send_event(SpawnEffectEvent::new(EffectKind::DeathFade(appearance, facing), position.visual_center()));
+        }
+        world.despawn(entity);
+        world.send_event(NewMessageEvent::new(message));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{add_status_effect, Position, Team};
+
+    use super::*;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn damage() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Health::new(10)).id();
+        assert_eq!(4, apply_damage(&mut world, entity, 4));
+        assert_eq!(6, world.get::<Health>(entity).unwrap().current);
+        assert_eq!(6, apply_damage(&mut world, entity, 8));
+        assert!(world.get::<Health>(entity).unwrap().is_dead());
+    }
+
+    #[test]
+    fn damage_through_shield() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Health::new(10)).id();
+        add_status_effect(&mut world, entity, StatusEffectKind::Shielded, None, 3);
+        assert_approx_eq!(0.3, absorb_percentage(&world, entity));
+        assert_eq!(2, apply_damage(&mut world, entity, 5));
+        assert_eq!(8, world.get::<Health>(entity).unwrap().current);
+        assert_approx_eq!(0.0, absorb_percentage(&world, entity));
+    }
+
+    #[test]
+    fn healing_capped() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Health::new(10)).id();
+        apply_damage(&mut world, entity, 3);
+        assert_eq!(3, apply_healing(&mut world, entity, 5));
+        assert_eq!(10, world.get::<Health>(entity).unwrap().current);
+    }
+
+    #[test]
+    fn dead_characters_removed() {
+        let mut world = World::new();
+        let enemy = world.spawn().insert(Character).insert(Position::new(2, 2)).insert(Health::new(5)).id();
+        let player = world
+            .spawn()
+            .insert(Character)
+            .insert(Player)
+            .insert(Position::new(4, 4))
+            .insert(Health::new(5))
+            .id();
+        let survivor = world.spawn().insert(Character).insert(Position::new(6, 6)).insert(Health::new(5)).id();
+        apply_damage(&mut world, enemy, 5);
+        apply_damage(&mut world, player, 5);
+        apply_damage(&mut world, survivor, 4);
+
+        remove_dead(&mut world);
+        assert!(world.get_entity(enemy).is_none());
+        assert!(world.get_entity(player).is_some());
+        assert!(world.get_entity(survivor).is_some());
+    }
+
+    #[test]
+    fn death_messages_follow_team() {
+        let mut world = World::new();
+        world.spawn().insert(Character).insert(Player).insert(Team::Player).insert(Health::new(5));
+        let foe = world.spawn().insert(Character).insert(Team::Enemy).insert(Health::new(5)).id();
+        let golem = world.spawn().insert(Character).insert(Team::Player).insert(Health::new(5)).id();
+        apply_damage(&mut world, foe, 5);
+        apply_damage(&mut world, golem, 5);
+        world.insert_resource(Events::<NewMessageEvent>::default());
+
+        remove_dead(&mut world);
+        let events = world.resource::<Events<NewMessageEvent>>();
+        let mut messages: Vec<String> = events.get_reader().iter(events).map(|e| e.message.clone()).collect();
+        messages.sort();
+        assert_eq!(vec!["A foe falls.".to_string(), "An ally falls.".to_string()], messages);
+    }
+}
@@ -0,0 +1,88 @@
+use super::{Direction, Point, Skill};
+
+#[derive(Clone, Debug)]
+pub enum PlayerCommand {
+    Move(Direction),
+    WalkTo(Point),
+    UseSkill(Skill, Point),
+}
+
+// Holds back player commands while the last turn is still animating. Only the UI
+// consults the gate, so headless simulation runs turns back to back without it.
+pub struct InputGate {
+    // Commands always run right away, cutting animations short
+    pub fast_mode: bool,
+    busy: bool,
+    queued: Option<PlayerCommand>,
+}
+
+impl InputGate {
+    pub fn new() -> Self {
+        InputGate {
+            fast_mode: false,
+            busy: false,
+            queued: None,
+        }
+    }
+
+    pub fn set_busy(&mut self, busy: bool) {
+        self.busy = busy;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.fast_mode || !self.busy
+    }
+
+    // Returns the command if it can run now. Otherwise it waits in the queue,
+    // replacing anything already waiting so at most one command is ever pending.
+    pub fn submit(&mut self, command: PlayerCommand) -> Option<PlayerCommand> {
+        if self.is_open() && self.queued.is_none() {
+            Some(command)
+        } else {
+            self.queued = Some(command);
+            None
+        }
+    }
+
+    // The queued command, once the gate has opened
+    pub fn take_ready(&mut self) -> Option<PlayerCommand> {
+        if self.is_open() {
+            self.queued.take()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_runs_immediately() {
+        let mut gate = InputGate::new();
+        assert!(gate.submit(PlayerCommand::Move(Direction::North)).is_some());
+        assert!(gate.take_ready().is_none());
+    }
+
+    #[test]
+    fn busy_queues_latest() {
+        let mut gate = InputGate::new();
+        gate.set_busy(true);
+        assert!(gate.submit(PlayerCommand::Move(Direction::North)).is_none());
+        assert!(gate.submit(PlayerCommand::Move(Direction::South)).is_none());
+        assert!(gate.take_ready().is_none());
+
+        gate.set_busy(false);
+        assert!(matches!(gate.take_ready(), Some(PlayerCommand::Move(Direction::South))));
+        assert!(gate.take_ready().is_none());
+    }
+
+    #[test]
+    fn fast_mode_skips_waiting() {
+        let mut gate = InputGate::new();
+        gate.fast_mode = true;
+        gate.set_busy(true);
+        assert!(gate.submit(PlayerCommand::WalkTo(Point::new(2, 2))).is_some());
+    }
+}
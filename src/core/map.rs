@@ -1,6 +1,6 @@
 use std::io::{Read, Write};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 
 use super::utils::Point;
@@ -14,6 +14,36 @@ pub enum MapKind {
     Winter,
 }
 
+impl MapKind {
+    pub const ALL: [MapKind; 5] = [MapKind::Ashlands, MapKind::Beach, MapKind::Desert, MapKind::Ruins, MapKind::Winter];
+
+    pub fn folder_name(&self) -> &'static str {
+        match self {
+            MapKind::Ashlands => "ashlands",
+            MapKind::Beach => "beach",
+            MapKind::Desert => "desert",
+            MapKind::Ruins => "ruins",
+            MapKind::Winter => "winter",
+        }
+    }
+
+    pub fn from_folder_name(name: &str) -> Option<MapKind> {
+        MapKind::ALL.iter().copied().find(|k| k.folder_name() == name)
+    }
+
+    pub fn folder_path(&self) -> String {
+        format!("/maps/{}", self.folder_name())
+    }
+
+    pub fn data_path(&self, variant: u32) -> String {
+        format!("/maps/{}/map{}.dat", self.folder_name(), variant)
+    }
+
+    pub fn image_path(&self, variant: u32) -> String {
+        format!("/maps/{}/map{}.png", self.folder_name(), variant)
+    }
+}
+
 #[derive(Copy, Clone, Serialize, Deserialize, Debug)]
 pub struct MapTile {
     walkable: bool,
@@ -23,15 +53,23 @@ pub struct MapTile {
 pub struct Map {
     tiles: [[MapTile; Map::MAX_TILES]; Map::MAX_TILES],
     pub kind: MapKind,
+    // Which of the biome's maps this is, taken from the file name rather than stored in it
+    #[serde(skip)]
+    pub variant: u32,
 }
 
 impl Map {
     pub const MAX_TILES: usize = super::utils::MAX_POINT_SIZE as usize;
+    // Variants are numbered from one on disk, map1.dat being the first
+    pub const FIRST_VARIANT: u32 = 1;
 
-    pub fn load(file: &mut ggez::filesystem::File) -> Result<Map> {
+    // Either a file in the game's virtual filesystem or one read directly from disk,
+    // with the variant coming from its file name as the data does not record it
+    pub fn load(mut reader: impl Read, variant: u32) -> Result<Map> {
         let mut data = vec![];
-        file.read_to_end(&mut data)?;
-        Ok(bincode::deserialize(&data)?)
+        reader.read_to_end(&mut data)?;
+        let map: Map = bincode::deserialize(&data)?;
+        Ok(map.with_variant(variant))
     }
 
     #[allow(dead_code)]
@@ -39,9 +77,23 @@ impl Map {
         Map {
             tiles: [[MapTile { walkable: true }; Map::MAX_TILES]; Map::MAX_TILES],
             kind,
+            variant: Map::FIRST_VARIANT,
         }
     }
 
+    pub fn with_variant(mut self, variant: u32) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    pub fn data_path(&self) -> String {
+        self.kind.data_path(self.variant)
+    }
+
+    pub fn image_path(&self) -> String {
+        self.kind.image_path(self.variant)
+    }
+
     pub fn is_walkable(&self, position: &Point) -> bool {
         self.tiles[position.x as usize][position.y as usize].walkable
     }
@@ -58,3 +110,165 @@ impl Map {
         Ok(())
     }
 }
+
+// Which map to start a battle on, from the command line such as "beach" or "beach:2"
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapRequest {
+    Any,
+    Biome(MapKind),
+    Exact(MapKind, u32),
+}
+
+impl MapRequest {
+    pub fn parse(text: &str) -> Result<MapRequest> {
+        if text == "random" {
+            return Ok(MapRequest::Any);
+        }
+        let (biome, variant) = match text.split_once(':') {
+            Some((biome, variant)) => (biome, Some(variant)),
+            None => (text, None),
+        };
+        let kind = MapKind::from_folder_name(&biome.to_lowercase()).ok_or_else(|| anyhow!("Unknown map biome {}", biome))?;
+        match variant {
+            Some(variant) => Ok(MapRequest::Exact(
+                kind,
+                variant.parse().map_err(|_| anyhow!("Invalid map variant {}", variant))?,
+            )),
+            None => Ok(MapRequest::Biome(kind)),
+        }
+    }
+}
+
+// Every map in the data, found from file names of the form /maps/<biome>/map<variant>.dat
+#[derive(Debug, Default)]
+pub struct MapIndex {
+    maps: Vec<(MapKind, u32)>,
+}
+
+impl MapIndex {
+    pub fn from_paths<S: AsRef<str>>(paths: &[S]) -> MapIndex {
+        let mut maps: Vec<(MapKind, u32)> = paths.iter().filter_map(|p| MapIndex::parse_path(p.as_ref())).collect();
+        maps.sort_by_key(|(kind, variant)| (kind.folder_name(), *variant));
+        maps.dedup();
+        MapIndex { maps }
+    }
+
+    pub fn parse_path(path: &str) -> Option<(MapKind, u32)> {
+        let mut parts = path.trim_start_matches('/').split('/');
+        if parts.next() != Some("maps") {
+            return None;
+        }
+        let kind = MapKind::from_folder_name(parts.next()?)?;
+        let variant = parts.next()?.strip_prefix("map")?.strip_suffix(".dat")?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some((kind, variant))
+    }
+
+    pub fn maps(&self) -> &[(MapKind, u32)] {
+        &self.maps
+    }
+
+    pub fn variants(&self, kind: MapKind) -> Vec<u32> {
+        self.maps.iter().filter(|(k, _)| *k == kind).map(|(_, variant)| *variant).collect()
+    }
+
+    // The seed picks between every map that fits, so the caller decides how random it is.
+    // The rest follow in order, to fall back on if the first will not do.
+    pub fn choices(&self, request: MapRequest, seed: u64) -> Vec<(MapKind, u32)> {
+        let mut candidates: Vec<(MapKind, u32)> = self
+            .maps
+            .iter()
+            .copied()
+            .filter(|(kind, variant)| match request {
+                MapRequest::Any => true,
+                MapRequest::Biome(biome) => *kind == biome,
+                MapRequest::Exact(biome, exact) => *kind == biome && *variant == exact,
+            })
+            .collect();
+        if !candidates.is_empty() {
+            let start = (seed % candidates.len() as u64) as usize;
+            candidates.rotate_left(start);
+        }
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_round_trip() {
+        let mut map = Map::empty(MapKind::Desert);
+        map.set_walkable(&Point::new(2, 3), false);
+        let data = bincode::serialize(&map).unwrap();
+
+        let loaded = Map::load(data.as_slice(), 2).unwrap();
+        assert_eq!(MapKind::Desert, loaded.kind);
+        assert_eq!(2, loaded.variant);
+        assert!(!loaded.is_walkable(&Point::new(2, 3)));
+        assert!(loaded.is_walkable(&Point::new(3, 2)));
+    }
+
+    #[test]
+    fn load_rejects_truncated() {
+        let data = bincode::serialize(&Map::empty(MapKind::Desert)).unwrap();
+        assert!(Map::load(&data[..data.len() / 2], Map::FIRST_VARIANT).is_err());
+    }
+
+    #[test]
+    fn index_from_paths() {
+        let index = MapIndex::from_paths(&[
+            "/maps/beach/map2.dat",
+            "/maps/beach/map1.dat",
+            "/maps/winter/map1.dat",
+            "/maps/winter/map1.png",
+            "/maps/swamp/map1.dat",
+            "/maps/beach/old/map3.dat",
+            "/images/map1.dat",
+        ]);
+        assert_eq!(&[(MapKind::Beach, 1), (MapKind::Beach, 2), (MapKind::Winter, 1)], index.maps());
+        assert_eq!(vec![1, 2], index.variants(MapKind::Beach));
+        assert!(index.variants(MapKind::Desert).is_empty());
+    }
+
+    #[test]
+    fn choose_map() {
+        let index = MapIndex::from_paths(&["/maps/beach/map1.dat", "/maps/beach/map2.dat", "/maps/ruins/map4.dat"]);
+        assert_eq!(vec![(MapKind::Ruins, 4)], index.choices(MapRequest::Biome(MapKind::Ruins), 7));
+        assert_eq!(vec![(MapKind::Beach, 2)], index.choices(MapRequest::Exact(MapKind::Beach, 2), 0));
+        assert!(index.choices(MapRequest::Exact(MapKind::Beach, 3), 0).is_empty());
+        assert!(index.choices(MapRequest::Biome(MapKind::Desert), 0).is_empty());
+        assert_eq!(
+            vec![(MapKind::Beach, 2), (MapKind::Beach, 1)],
+            index.choices(MapRequest::Biome(MapKind::Beach), 1)
+        );
+        assert_eq!(
+            vec![(MapKind::Ruins, 4), (MapKind::Beach, 1), (MapKind::Beach, 2)],
+            index.choices(MapRequest::Any, 5)
+        );
+    }
+
+    #[test]
+    fn parse_request() {
+        assert_eq!(MapRequest::Any, MapRequest::parse("random").unwrap());
+        assert_eq!(MapRequest::Biome(MapKind::Desert), MapRequest::parse("Desert").unwrap());
+        assert_eq!(MapRequest::Exact(MapKind::Beach, 2), MapRequest::parse("beach:2").unwrap());
+        assert!(MapRequest::parse("swamp").is_err());
+        assert!(MapRequest::parse("beach:two").is_err());
+    }
+
+    #[test]
+    fn variant_paths() {
+        let map = Map::empty(MapKind::Ashlands).with_variant(3);
+        assert_eq!("/maps/ashlands/map3.dat", map.data_path());
+        assert_eq!("/maps/ashlands/map3.png", map.image_path());
+
+        assert_eq!(Map::FIRST_VARIANT, Map::empty(MapKind::Ashlands).variant);
+        // The file name decides the variant, not whatever was saved
+        let data = bincode::serialize(&map).unwrap();
+        assert_eq!(1, Map::load(data.as_slice(), 1).unwrap().variant);
+    }
+}
@@ -23,12 +23,51 @@ pub use skill::*;
 mod field;
 pub use field::*;
 
+mod field_rules;
+pub use field_rules::*;
+
 mod physics;
 pub use physics::*;
 
 mod ecs;
 pub use ecs::*;
 
+mod health;
+pub use health::*;
+
+mod status;
+pub use status::*;
+
+mod turn;
+pub use turn::*;
+
+mod area;
+pub use area::*;
+
+mod movement;
+pub use movement::*;
+
+mod team;
+pub use team::*;
+
+mod summon;
+pub use summon::*;
+
+mod pathing;
+pub use pathing::*;
+
+mod input_gate;
+pub use input_gate::*;
+
+mod facing;
+pub use facing::*;
+
+mod projectile;
+pub use projectile::*;
+
+mod combat;
+pub use combat::*;
+
 #[derive(Component, Debug, Deserialize, Serialize)]
 pub struct Position {
     pub position: SizedPoint,
@@ -60,10 +99,16 @@ impl From<SizedPoint> for Position {
     }
 }
 
-pub fn create_game_world(fs: &mut ggez::filesystem::Filesystem) -> Result<World> {
+pub fn create_game_world(fs: &mut ggez::filesystem::Filesystem, kind: MapKind, variant: u32) -> Result<World> {
+    let map = Map::load(fs.open(kind.data_path(variant))?, variant)?;
+    Ok(create_game_world_with_map(map))
+}
+
+// Everything but reading the map, so the world can be built without the game's filesystem
+pub fn create_game_world_with_map(map: Map) -> World {
     let mut world = World::new();
 
-    setup_game_resources(&mut world, fs)?;
+    setup_game_resources(&mut world, map);
 
     world
         .spawn()
@@ -71,12 +116,39 @@ pub fn create_game_world(fs: &mut ggez::filesystem::Filesystem) -> Result<World>
         .insert(crate::ui::Animation::new())
         .insert(Appearance::new(AppearanceKind::MaleBrownHairBlueBody))
         .insert(Position::new(8, 6))
+        .insert(Health::new(20))
+        .insert(StatusEffects::new())
         .insert(Player)
+        .insert(Team::Player)
+        .insert(Facing::new(Direction::West))
         .insert(Skills::new(&[
             Skill::new("Shoot", SkillEffect::RangedAttack, TargetType::Enemy)
                 .with_range(24)
+                .with_damage(3)
+                .with_projectile(AppearanceKind::FireBolt)
                 .path_must_be_clear(),
             Skill::new("Dodge", SkillEffect::Move, TargetType::Tile).with_range(2).path_must_be_clear(),
+            Skill::new("Explosive Shot", SkillEffect::RangedAttack, TargetType::Any)
+                .with_range(6)
+                .with_area(AreaShape::Circle(1))
+                .with_damage(2)
+                .with_projectile(AppearanceKind::FireBolt)
+                .with_impact(PostMovementActionKind::SpawnField {
+                    kind: FieldKind::Fire,
+                    area: AreaShape::Single,
+                    duration: Some(3),
+                })
+                .path_must_be_clear(),
+            Skill::new("Shove", SkillEffect::Push(2), TargetType::AnyoneButSelf).with_range(1),
+            Skill::new(
+                "Summon Golem",
+                SkillEffect::Summon {
+                    template: "Golem".to_string(),
+                    lifetime: 5,
+                },
+                TargetType::Tile,
+            )
+            .with_range(3),
         ]));
 
     world
@@ -84,20 +156,43 @@ pub fn create_game_world(fs: &mut ggez::filesystem::Filesystem) -> Result<World>
         .insert(Character)
         .insert(Appearance::new(AppearanceKind::MaleBrownHairBlueBody))
         .insert(crate::ui::Animation::new())
-        .insert(Position::new(6, 6));
+        .insert(Position::new(6, 6))
+        .insert(Health::new(10))
+        .insert(StatusEffects::new())
+        .insert(Team::Enemy)
+        .insert(Facing::new(Direction::East));
 
     world
         .spawn()
         .insert(Character)
         .insert(Position::new_sized(3, 4, 2, 2))
         .insert(crate::ui::Animation::new())
-        .insert(Appearance::new(AppearanceKind::Golem));
+        .insert(Appearance::new(AppearanceKind::Golem))
+        .insert(Health::new(30))
+        .insert(StatusEffects::new())
+        .insert(Team::Enemy)
+        .insert(Facing::new(Direction::East));
 
     world
         .spawn()
         .insert(Fields::new(FieldColor::Gray, &[Point::new(7, 5), Point::new(7, 6), Point::new(7, 7)]));
 
-    Ok(world)
+    world
+        .spawn()
+        .insert(Fields::for_kind(FieldKind::Poison, &[Point::new(9, 9), Point::new(10, 9), Point::new(9, 10)]).with_duration(20));
+
+    world.spawn().insert(Fields::for_kind(FieldKind::Fire, &[Point::new(1, 10)]));
+    world.spawn().insert(Fields::for_kind(
+        FieldKind::Oil,
+        &[Point::new(2, 10), Point::new(3, 10), Point::new(4, 10), Point::new(4, 11)],
+    ));
+
+    world
+}
+
+// Characters the fixed starting layout leaves on a wall of the world's map, as the layout is the same on every map
+pub fn find_blocked_spawns(world: &mut World) -> Vec<Entity> {
+    world.resource_scope(|world, map: Mut<Map>| find_characters_blocked_by(world, &map))
 }
 
 pub fn create_game_schedule() -> Schedule {
@@ -108,21 +203,43 @@ pub fn create_game_schedule() -> Schedule {
     schedule
 }
 
-pub fn setup_game_resources(world: &mut World, fs: &mut ggez::filesystem::Filesystem) -> Result<()> {
+pub fn setup_game_resources(world: &mut World, map: Map) {
     world.insert_resource(utils::Frame::zero());
     world.insert_resource(Log::new());
+    world.insert_resource(FieldRuleLog::new());
+    world.insert_resource(TeamRelations::new());
+    world.insert_resource(SummonTemplates::new());
+    world.insert_resource(InputGate::new());
 
-    let map = Map::load(&mut fs.open("/maps/beach/map1.dat")?)?;
     world.insert_resource(map);
 
     world.insert_resource(Events::<NewMessageEvent>::default());
     world.insert_resource(Events::<ScrollMessageEvent>::default());
-
-    Ok(())
+    world.insert_resource(Events::<CombatEvent>::default());
 }
 
 // Since we aren't using Bevy's App model, we have to clear our event buffers by hand
-pub fn clear_event_buffers(mut a: ResMut<Events<NewMessageEvent>>, mut b: ResMut<Events<ScrollMessageEvent>>) {
+pub fn clear_event_buffers(mut a: ResMut<Events<NewMessageEvent>>, mut b: ResMut<Events<ScrollMessageEvent>>, mut c: ResMut<Events<CombatEvent>>) {
     a.update();
     b.update();
+    c.update();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawns_on_walls() {
+        let mut world = create_game_world_with_map(Map::empty(MapKind::Beach));
+        assert!(find_blocked_spawns(&mut world).is_empty());
+
+        let mut map = Map::empty(MapKind::Beach);
+        map.set_walkable(&Point::new(8, 6), false);
+        map.set_walkable(&Point::new(4, 5), false);
+        let mut world = create_game_world_with_map(map);
+        let blocked = find_blocked_spawns(&mut world);
+        assert_eq!(2, blocked.len());
+        assert!(blocked.iter().any(|e| world.get::<Player>(*e).is_some()));
+    }
 }
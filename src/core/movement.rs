@@ -0,0 +1,331 @@
+use bevy_ecs::prelude::*;
+
+use super::{
+    apply_damage, cuts_corner, end_turn, find_character_at_location, find_position, Character, Direction, Facing, Map, NewMessageEvent, Point, Position,
+    SizedPoint,
+};
+use crate::ui::MovementAnimationEvent;
+
+pub const COLLISION_DAMAGE: u32 = 2;
+
+enum Blocker {
+    Wall,
+    Character(Entity),
+}
+
+// The first wall or character in the area, passing over anyone in ignore
+fn find_blocker(world: &mut World, ignore: &[Entity], area: &[Point]) -> Option<Blocker> {
+    let map = world.get_resource::<Map>().unwrap();
+    if area.iter().any(|p| !p.in_bounds() || !map.is_walkable(p)) {
+        return Some(Blocker::Wall);
+    }
+    let mut query = world.query_filtered::<(Entity, &Position), With<Character>>();
+    for (other, position) in query.iter(world) {
+        if !ignore.contains(&other) && area.iter().any(|p| position.position.contains_point(p)) {
+            return Some(Blocker::Character(other));
+        }
+    }
+    None
+}
+
+fn set_position(world: &mut World, entity: Entity, initial: SizedPoint, position: SizedPoint) {
+    world.get_mut::<Position>(entity).unwrap().position = position;
+    world.send_event(MovementAnimationEvent::new(entity, initial.visual_center(), position.visual_center()));
+}
+
+// A single voluntary step, which uses up the turn only if it succeeds
+pub fn try_move(world: &mut World, entity: Entity, direction: Direction) -> bool {
+    let moved = try_step(world, entity, direction);
+    if moved {
+        end_turn(world);
+    }
+    moved
+}
+
+// Every voluntary movement goes one checked step at a time through here, saying why when it can not.
+// Whoever asked for the steps decides whether they used up the turn.
+pub fn try_step(world: &mut World, entity: Entity, direction: Direction) -> bool {
+    let initial = match find_position(world, entity) {
+        Some(initial) => initial,
+        None => return false,
+    };
+
+    let reason = match initial.in_direction(direction) {
+        None => Some("You can't leave the arena."),
+        Some(_) if cuts_corner(world, initial, direction) => Some("Can't squeeze past the corner."),
+        Some(next) => match find_blocker(world, &[entity], &next.covered_points()) {
+            Some(Blocker::Wall) => Some("The way is blocked."),
+            Some(Blocker::Character(_)) => Some("Someone is in the way."),
+            None => {
+                set_position(world, entity, initial, next);
+                if let Some(mut facing) = world.get_mut::<Facing>(entity) {
+                    facing.direction = direction;
+                }
+                None
+            }
+        },
+    };
+
+    match reason {
+        Some(reason) => {
+            world.send_event(NewMessageEvent::new(reason));
+            false
+        }
+        None => true,
+    }
+}
+
+// Steps straight at the target until there or stopped, returning how many were taken
+pub fn step_towards(world: &mut World, entity: Entity, target: Point) -> u32 {
+    let mut steps = 0;
+    while let Some(current) = find_position(world, entity) {
+        if current.origin == target || !try_step(world, entity, Direction::from_two_points(&current, &SizedPoint::from(target))) {
+            break;
+        }
+        steps += 1;
+    }
+    steps
+}
+
+// Slides the entity up to distance tiles, stopping early at walls or other characters.
+// Slamming into something hurts, unless it is the ignored entity (a puller for example).
+pub fn force_move(world: &mut World, entity: Entity, direction: Direction, distance: u32, ignore: Option<Entity>) -> u32 {
+    let initial = match find_position(world, entity) {
+        Some(initial) if direction != Direction::None => initial,
+        _ => return 0,
+    };
+
+    let mut current = initial;
+    let mut moved = 0;
+    let mut collision = None;
+    while moved < distance {
+        let next = match current.in_direction(direction) {
+            Some(next) => next,
+            None => {
+                collision = Some(Blocker::Wall);
+                break;
+            }
+        };
+        if let Some(blocker) = find_blocker(world, &[entity], &next.covered_points()) {
+            collision = Some(blocker);
+            break;
+        }
+        current = next;
+        moved += 1;
+    }
+
+    if moved > 0 {
+        set_position(world, entity, initial, current);
+    }
+
+    match collision {
+        Some(Blocker::Character(other)) if Some(other) == ignore => {}
+        Some(Blocker::Character(other)) => {
+            world.send_event(NewMessageEvent::new("Collision!"));
+            apply_damage(world, entity, COLLISION_DAMAGE);
+            apply_damage(world, other, COLLISION_DAMAGE);
+        }
+        Some(Blocker::Wall) => {
+            world.send_event(NewMessageEvent::new("Collision!"));
+            apply_damage(world, entity, COLLISION_DAMAGE);
+        }
+        None => {}
+    }
+    moved
+}
+
+// Measured between the closest tiles so large characters are pushed straight
+fn direction_between(world: &mut World, from: Entity, to: Entity) -> Direction {
+    let from = find_position(world, from).unwrap();
+    let to = find_position(world, to).unwrap();
+    let start = from.nearest_point_to(to);
+    let end = to.nearest_point_to(SizedPoint::from(start));
+    Direction::from_two_points(&SizedPoint::from(start), &SizedPoint::from(end))
+}
+
+pub fn push(world: &mut World, invoker: Entity, target: Entity, distance: u32) -> u32 {
+    let direction = direction_between(world, invoker, target);
+    force_move(world, target, direction, distance, None)
+}
+
+pub fn pull(world: &mut World, invoker: Entity, target: Entity, distance: u32) -> u32 {
+    let direction = direction_between(world, target, invoker);
+    force_move(world, target, direction, distance, Some(invoker))
+}
+
+pub fn swap(world: &mut World, invoker: Entity, target: Entity) -> bool {
+    let invoker_position = find_position(world, invoker).unwrap();
+    let target_position = find_position(world, target).unwrap();
+
+    // Large characters may not fit where small ones stood
+    let new_invoker_position = invoker_position.move_to(target_position.origin);
+    let new_target_position = target_position.move_to(invoker_position.origin);
+    // Neighbours of different sizes can land on top of each other
+    if new_invoker_position.covered_points().iter().any(|p| new_target_position.contains_point(p)) {
+        world.send_event(NewMessageEvent::new("No room to swap."));
+        return false;
+    }
+    // Both are leaving, so only a third character or a wall can be in the way
+    for position in [new_invoker_position, new_target_position] {
+        if find_blocker(world, &[invoker, target], &position.covered_points()).is_some() {
+            world.send_event(NewMessageEvent::new("No room to swap."));
+            return false;
+        }
+    }
+
+    set_position(world, invoker, invoker_position, new_invoker_position);
+    set_position(world, target, target_position, new_target_position);
+    true
+}
+
+pub fn find_forced_movement_target(world: &mut World, invoker: Entity, target: Point) -> Option<Entity> {
+    find_character_at_location(world, target).filter(|t| *t != invoker)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Health, MapKind};
+
+    use super::*;
+
+    fn movement_test_world() -> (World, Entity, Entity) {
+        let mut world = World::new();
+        let invoker = world.spawn().insert(Character).insert(Position::new(2, 2)).insert(Health::new(10)).id();
+        let target = world.spawn().insert(Character).insert(Position::new(3, 2)).insert(Health::new(10)).id();
+        world.insert_resource(Map::empty(MapKind::Ashlands));
+        (world, invoker, target)
+    }
+
+    fn health(world: &World, entity: Entity) -> u32 {
+        world.get::<Health>(entity).unwrap().current
+    }
+
+    #[test]
+    fn try_move_steps() {
+        let (mut world, _, target) = movement_test_world();
+        assert!(try_move(&mut world, target, Direction::SouthEast));
+        assert_eq!(Point::new(4, 3), find_position(&mut world, target).unwrap().origin);
+    }
+
+    #[test]
+    fn try_move_rejected() {
+        let (mut world, invoker, target) = movement_test_world();
+        world.get_resource_mut::<Map>().unwrap().set_walkable(&Point::new(4, 2), false);
+        world.get_resource_mut::<Map>().unwrap().set_walkable(&Point::new(3, 1), false);
+
+        assert!(!try_move(&mut world, target, Direction::West));
+        assert!(!try_move(&mut world, target, Direction::East));
+        assert!(!try_move(&mut world, target, Direction::North));
+        assert!(!try_move(&mut world, target, Direction::NorthEast));
+        assert_eq!(Point::new(3, 2), find_position(&mut world, target).unwrap().origin);
+
+        world.get_mut::<Position>(invoker).unwrap().position = SizedPoint::new(2, 0);
+        assert!(!try_move(&mut world, invoker, Direction::North));
+    }
+
+    #[test]
+    fn try_move_large_footprint() {
+        let (mut world, _, target) = movement_test_world();
+        world.get_mut::<Position>(target).unwrap().position = SizedPoint::new_sized(5, 5, 2, 2);
+        world.get_resource_mut::<Map>().unwrap().set_walkable(&Point::new(6, 7), false);
+        assert!(!try_move(&mut world, target, Direction::South));
+        assert!(try_move(&mut world, target, Direction::North));
+    }
+
+    #[test]
+    fn push_full_distance() {
+        let (mut world, invoker, target) = movement_test_world();
+        assert_eq!(3, push(&mut world, invoker, target, 3));
+        assert_eq!(Point::new(6, 2), find_position(&mut world, target).unwrap().origin);
+        assert_eq!(10, health(&world, target));
+    }
+
+    #[test]
+    fn push_into_wall() {
+        let (mut world, invoker, target) = movement_test_world();
+        world.get_resource_mut::<Map>().unwrap().set_walkable(&Point::new(5, 2), false);
+        assert_eq!(1, push(&mut world, invoker, target, 3));
+        assert_eq!(Point::new(4, 2), find_position(&mut world, target).unwrap().origin);
+        assert_eq!(10 - COLLISION_DAMAGE, health(&world, target));
+    }
+
+    #[test]
+    fn push_off_map_edge() {
+        let (mut world, invoker, target) = movement_test_world();
+        world.get_mut::<Position>(target).unwrap().position = SizedPoint::new(11, 2);
+        assert_eq!(1, push(&mut world, invoker, target, 3));
+        assert_eq!(Point::new(12, 2), find_position(&mut world, target).unwrap().origin);
+        assert_eq!(10 - COLLISION_DAMAGE, health(&world, target));
+    }
+
+    #[test]
+    fn push_into_character() {
+        let (mut world, invoker, target) = movement_test_world();
+        let other = world
+            .spawn()
+            .insert(Character)
+            .insert(Position::new_sized(5, 1, 2, 2))
+            .insert(Health::new(10))
+            .id();
+        assert_eq!(1, push(&mut world, invoker, target, 3));
+        assert_eq!(10 - COLLISION_DAMAGE, health(&world, target));
+        assert_eq!(10 - COLLISION_DAMAGE, health(&world, other));
+    }
+
+    #[test]
+    fn push_large() {
+        let (mut world, invoker, target) = movement_test_world();
+        world.get_mut::<Position>(target).unwrap().position = SizedPoint::new_sized(2, 3, 2, 2);
+        world.get_resource_mut::<Map>().unwrap().set_walkable(&Point::new(3, 6), false);
+        assert_eq!(1, push(&mut world, invoker, target, 3));
+        assert_eq!(Point::new(2, 4), find_position(&mut world, target).unwrap().origin);
+    }
+
+    #[test]
+    fn pull_stops_at_invoker() {
+        let (mut world, invoker, target) = movement_test_world();
+        world.get_mut::<Position>(target).unwrap().position = SizedPoint::new(6, 2);
+        assert_eq!(3, pull(&mut world, invoker, target, 5));
+        assert_eq!(Point::new(3, 2), find_position(&mut world, target).unwrap().origin);
+        assert_eq!(10, health(&world, target));
+        assert_eq!(10, health(&world, invoker));
+    }
+
+    #[test]
+    fn swap_places() {
+        let (mut world, invoker, target) = movement_test_world();
+        world.get_mut::<Position>(target).unwrap().position = SizedPoint::new(6, 6);
+        assert!(swap(&mut world, invoker, target));
+        assert_eq!(Point::new(6, 6), find_position(&mut world, invoker).unwrap().origin);
+        assert_eq!(Point::new(2, 2), find_position(&mut world, target).unwrap().origin);
+    }
+
+    #[test]
+    fn swap_needs_room() {
+        let (mut world, invoker, target) = movement_test_world();
+        world.get_mut::<Position>(target).unwrap().position = SizedPoint::new_sized(6, 6, 2, 2);
+        world.get_resource_mut::<Map>().unwrap().set_walkable(&Point::new(3, 3), false);
+        assert!(!swap(&mut world, invoker, target));
+        assert_eq!(Point::new(2, 2), find_position(&mut world, invoker).unwrap().origin);
+    }
+
+    #[test]
+    fn swap_cannot_overlap() {
+        let (mut world, invoker, target) = movement_test_world();
+        world.get_mut::<Position>(target).unwrap().position = SizedPoint::new_sized(3, 2, 2, 2);
+        assert!(!swap(&mut world, invoker, target));
+        assert_eq!(Point::new(2, 2), find_position(&mut world, invoker).unwrap().origin);
+        assert_eq!(Point::new(3, 2), find_position(&mut world, target).unwrap().origin);
+    }
+
+    #[test]
+    fn swap_blocked_by_bystander() {
+        let (mut world, invoker, target) = movement_test_world();
+        world.get_mut::<Position>(target).unwrap().position = SizedPoint::new_sized(6, 6, 2, 2);
+        // Inside the large target's new footprint, alongside the invoker's old tile
+        world.spawn().insert(Character).insert(Position::new(3, 3));
+        assert!(!swap(&mut world, invoker, target));
+        assert_eq!(Point::new(2, 2), find_position(&mut world, invoker).unwrap().origin);
+        assert_eq!(Point::new(6, 6), find_position(&mut world, target).unwrap().origin);
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy_ecs::prelude::*;
+
+use super::{find_position, is_area_clear_of_others, movement_modifier, try_step, Direction, Map, Point, SizedPoint};
+
+// Tiles a character may walk in a single turn before haste or slow
+pub const BASE_MOVEMENT_POINTS: u32 = 3;
+
+// Straight steps are tried first so paths prefer them when the length is the same
+const PATH_DIRECTIONS: [Direction; 8] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+    Direction::NorthEast,
+    Direction::SouthEast,
+    Direction::SouthWest,
+    Direction::NorthWest,
+];
+
+// A diagonal step may not squeeze between two tiles when either is a wall
+pub fn cuts_corner(world: &World, position: SizedPoint, direction: Direction) -> bool {
+    let map = world.get_resource::<Map>().unwrap();
+    match direction.split_diagonal() {
+        Some((vertical, horizontal)) => [vertical, horizontal].iter().any(|d| match position.in_direction(*d) {
+            Some(side) => side.covered_points().iter().any(|p| !p.in_bounds() || !map.is_walkable(p)),
+            None => true,
+        }),
+        None => false,
+    }
+}
+
+pub fn movement_points(world: &World, entity: Entity) -> u32 {
+    std::cmp::max(BASE_MOVEMENT_POINTS as i32 + movement_modifier(world, entity), 0) as u32
+}
+
+// Shortest walk that ends with the entity's origin on the target, not including
+// the starting position. None if the target can not be reached or is where we stand.
+pub fn find_path(world: &mut World, entity: Entity, target: Point) -> Option<Vec<SizedPoint>> {
+    let start = find_position(world, entity)?;
+    if start.origin == target || !target.in_bounds() {
+        return None;
+    }
+
+    let mut came_from: HashMap<Point, SizedPoint> = HashMap::new();
+    let mut frontier = VecDeque::from([start]);
+    while let Some(current) = frontier.pop_front() {
+        if current.origin == target {
+            let mut path = vec![current];
+            let mut step = current;
+            while let Some(previous) = came_from.get(&step.origin) {
+                if previous.origin == start.origin {
+                    break;
+                }
+                path.push(*previous);
+                step = *previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        for direction in PATH_DIRECTIONS {
+            if let Some(next) = current.in_direction(direction) {
+                if next.origin == start.origin || came_from.contains_key(&next.origin) || cuts_corner(world, current, direction) {
+                    continue;
+                }
+                let covered = next.covered_points();
+                if covered.iter().all(|p| p.in_bounds()) && is_area_clear_of_others(world, &covered, Some(entity)) {
+                    came_from.insert(next.origin, current);
+                    frontier.push_back(next);
+                }
+            }
+        }
+    }
+    None
+}
+
+// Each step is checked and animated in turn, so the character follows the path instead of sliding
+// past corners and stops if it has become blocked. Returns how many steps were taken.
+pub fn move_along_path(world: &mut World, entity: Entity, path: &[SizedPoint]) -> u32 {
+    let mut moved = 0;
+    for step in path {
+        let current = match find_position(world, entity) {
+            Some(current) => current,
+            None => break,
+        };
+        if !try_step(world, entity, Direction::from_two_points(&current, step)) {
+            break;
+        }
+        moved += 1;
+    }
+    moved
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{add_status_effect, Character, MapKind, Position, StatusEffectKind};
+
+    use super::*;
+
+    fn pathing_test_world() -> (World, Entity) {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Character).insert(Position::new(2, 2)).id();
+        world.insert_resource(Map::empty(MapKind::Ashlands));
+        (world, entity)
+    }
+
+    #[test]
+    fn straight_path() {
+        let (mut world, entity) = pathing_test_world();
+        let path = find_path(&mut world, entity, Point::new(2, 5)).unwrap();
+        assert_eq!(vec![SizedPoint::new(2, 3), SizedPoint::new(2, 4), SizedPoint::new(2, 5)], path);
+    }
+
+    #[test]
+    fn path_around_obstacles() {
+        let (mut world, entity) = pathing_test_world();
+        world.get_resource_mut::<Map>().unwrap().set_walkable(&Point::new(2, 3), false);
+        world.spawn().insert(Character).insert(Position::new(1, 3));
+        let path = find_path(&mut world, entity, Point::new(2, 4)).unwrap();
+        assert_eq!(4, path.len());
+        assert_eq!(Point::new(2, 4), path.last().unwrap().origin);
+        assert!(!path.iter().any(|p| p.origin == Point::new(2, 3) || p.origin == Point::new(1, 3)));
+    }
+
+    #[test]
+    fn no_path() {
+        let (mut world, entity) = pathing_test_world();
+        assert!(find_path(&mut world, entity, Point::new(2, 2)).is_none());
+        assert!(find_path(&mut world, entity, Point::new(2, 13)).is_none());
+
+        world.spawn().insert(Character).insert(Position::new(6, 6));
+        assert!(find_path(&mut world, entity, Point::new(6, 6)).is_none());
+    }
+
+    #[test]
+    fn large_path_needs_room() {
+        let (mut world, entity) = pathing_test_world();
+        world.get_mut::<Position>(entity).unwrap().position = SizedPoint::new_sized(2, 2, 2, 2);
+        // A one tile gap in a wall is too narrow
+        for y in 0..13 {
+            if y != 6 {
+                world.get_resource_mut::<Map>().unwrap().set_walkable(&Point::new(5, y), false);
+            }
+        }
+        assert!(find_path(&mut world, entity, Point::new(7, 2)).is_none());
+
+        world.get_resource_mut::<Map>().unwrap().set_walkable(&Point::new(5, 7), true);
+        let path = find_path(&mut world, entity, Point::new(7, 2)).unwrap();
+        assert!(path.iter().any(|p| p.origin == Point::new(5, 6)));
+    }
+
+    #[test]
+    fn diagonal_path() {
+        let (mut world, entity) = pathing_test_world();
+        let path = find_path(&mut world, entity, Point::new(4, 4)).unwrap();
+        assert_eq!(vec![SizedPoint::new(3, 3), SizedPoint::new(4, 4)], path);
+    }
+
+    #[test]
+    fn no_corner_cutting() {
+        let (mut world, entity) = pathing_test_world();
+        world.get_resource_mut::<Map>().unwrap().set_walkable(&Point::new(3, 2), false);
+        assert!(cuts_corner(&world, SizedPoint::new(2, 2), Direction::NorthEast));
+        assert!(cuts_corner(&world, SizedPoint::new(2, 2), Direction::SouthEast));
+        assert!(!cuts_corner(&world, SizedPoint::new(2, 2), Direction::SouthWest));
+        assert!(!cuts_corner(&world, SizedPoint::new(2, 2), Direction::East));
+
+        let path = find_path(&mut world, entity, Point::new(3, 3)).unwrap();
+        assert_eq!(2, path.len());
+    }
+
+    #[test]
+    fn walk_moves_to_end() {
+        let (mut world, entity) = pathing_test_world();
+        let path = find_path(&mut world, entity, Point::new(4, 3)).unwrap();
+        assert_eq!(2, move_along_path(&mut world, entity, &path));
+        assert_eq!(Point::new(4, 3), find_position(&mut world, entity).unwrap().origin);
+    }
+
+    #[test]
+    fn walk_stops_when_blocked() {
+        let (mut world, entity) = pathing_test_world();
+        let path = find_path(&mut world, entity, Point::new(2, 5)).unwrap();
+        // Arrives after the path was found
+        world.spawn().insert(Character).insert(Position::new(2, 4));
+        assert_eq!(1, move_along_path(&mut world, entity, &path));
+        assert_eq!(Point::new(2, 3), find_position(&mut world, entity).unwrap().origin);
+    }
+
+    #[test]
+    fn movement_points_with_effects() {
+        let (mut world, entity) = pathing_test_world();
+        assert_eq!(BASE_MOVEMENT_POINTS, movement_points(&world, entity));
+        add_status_effect(&mut world, entity, StatusEffectKind::Slowed, Some(2), 1);
+        assert_eq!(BASE_MOVEMENT_POINTS - 1, movement_points(&world, entity));
+    }
+}
@@ -1,6 +1,6 @@
 use bevy_ecs::prelude::*;
 
-use super::{find_player, Character, Map, Point, Position};
+use super::{is_allied, Character, Map, Point, Position};
 
 pub fn is_area_clear_of_others(world: &mut World, area: &[Point], invoker: Option<Entity>) -> bool {
     world.resource_scope(|world, map: Mut<Map>| {
@@ -32,14 +32,41 @@ pub fn find_character_at_location(world: &mut World, area: Point) -> Option<Enti
     None
 }
 
-pub fn is_player_or_ally(world: &mut World, entity: Entity) -> bool {
-    // TODO - Ally
-    find_player(world) == entity
+// Characters touching any of the points, each only once even if large
+pub fn find_characters_in_area(world: &mut World, area: &[Point]) -> Vec<Entity> {
+    let mut query = world.query_filtered::<(Entity, &Position), With<Character>>();
+    let mut found = vec![];
+    for p in area {
+        for (entity, position) in query.iter(world) {
+            if position.position.contains_point(p) && !found.contains(&entity) {
+                found.push(entity);
+            }
+        }
+    }
+    found
+}
+
+// Characters in the area that an attack by the invoker hurts, which spares its allies and itself unless friendly fire is on
+pub fn find_targets_in_area(world: &mut World, invoker: Entity, area: &[Point], friendly_fire: bool) -> Vec<Entity> {
+    find_characters_in_area(world, area)
+        .into_iter()
+        .filter(|character| friendly_fire || !is_allied(world, invoker, *character))
+        .collect()
+}
+
+// Characters that would be left standing on a wall if the map were swapped for this one
+pub fn find_characters_blocked_by(world: &mut World, map: &Map) -> Vec<Entity> {
+    let mut query = world.query_filtered::<(Entity, &Position), With<Character>>();
+    query
+        .iter(world)
+        .filter(|(_, position)| position.position.covered_points().iter().any(|p| !map.is_walkable(p)))
+        .map(|(entity, _)| entity)
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::core::{MapKind, Player};
+    use crate::core::MapKind;
 
     use super::*;
 
@@ -55,12 +82,12 @@ mod tests {
     }
 
     #[test]
-    fn is_player() {
+    fn find_characters_in_area_once() {
         let mut world = World::new();
-        let first = world.spawn().insert(Character).insert(Player).id();
-        let second = world.spawn().insert(Character).id();
-        assert!(is_player_or_ally(&mut world, first));
-        assert!(!is_player_or_ally(&mut world, second));
+        let first = world.spawn().insert(Character).insert(Position::new_sized(6, 6, 2, 2)).id();
+        let second = world.spawn().insert(Character).insert(Position::new(3, 3)).id();
+        let area = [Point::new(3, 3), Point::new(6, 6), Point::new(7, 7), Point::new(0, 0)];
+        assert_eq!(vec![second, first], find_characters_in_area(&mut world, &area));
     }
 
     #[test]
@@ -82,4 +109,17 @@ mod tests {
         assert!(!is_area_clear_of_others(&mut world, &[Point::new(2, 4)], None));
         assert!(!is_area_clear_of_others(&mut world, &[Point::new(13, 14)], None));
     }
+
+    #[test]
+    fn blocked_by_map() {
+        let mut world = World::new();
+        let large = world.spawn().insert(Character).insert(Position::new_sized(6, 6, 2, 2)).id();
+        world.spawn().insert(Character).insert(Position::new(3, 3));
+        let mut map = Map::empty(MapKind::Ashlands);
+        assert!(find_characters_blocked_by(&mut world, &map).is_empty());
+
+        map.set_walkable(&Point::new(7, 7), false);
+        map.set_walkable(&Point::new(4, 4), false);
+        assert_eq!(vec![large], find_characters_blocked_by(&mut world, &map));
+    }
 }
@@ -0,0 +1,442 @@
+use std::iter::once;
+
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    add_status_effect, affected_area, apply_damage, find_characters_in_area, find_position, find_targets_in_area, is_hostile, remove_dead, send_combat_event,
+    Appearance, AppearanceKind, AreaShape, Character, CombatEvent, CombatEventKind, FieldKind, Fields, Point, Position, SizedPoint, StatusEffectKind,
+};
+use crate::ui::{Animation, EffectKind, MovementAnimationEvent, PostMovementAction, SpawnEffectEvent};
+
+// What to do once something finishes moving. Skills list these to build up how their shots land.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum PostMovementActionKind {
+    Despawn,
+    // Hands the landed shot back to core to deal its damage
+    ResolveProjectile,
+    // Damages everyone in the shape around where it landed
+    Explode {
+        area: AreaShape,
+        damage: u32,
+    },
+    // Leaves a field covering the shape around where it landed
+    SpawnField {
+        kind: FieldKind,
+        area: AreaShape,
+        duration: Option<u32>,
+    },
+    // Applies to everyone the shot hit
+    ApplyEffect {
+        kind: StatusEffectKind,
+        duration: Option<u32>,
+        stacks: u32,
+    },
+    // Jumps to the closest hostile character in range that the chain has not hit yet,
+    // running the listed actions when it lands there
+    Chain {
+        range: u32,
+        actions: Vec<PostMovementActionKind>,
+    },
+}
+
+// A shot in flight, which hits once its movement animation lands
+#[derive(Component, Clone, Debug)]
+pub struct Projectile {
+    pub invoker: Entity,
+    pub area: Vec<Point>,
+    pub damage: u32,
+    // Characters hit earlier in a chain, which it will not jump back to
+    pub struck: Vec<Entity>,
+    // Also hurts allies and the invoker caught in the area
+    pub friendly_fire: bool,
+}
+
+impl Projectile {
+    pub fn new(invoker: Entity, area: Vec<Point>, damage: u32) -> Self {
+        Projectile {
+            invoker,
+            area,
+            damage,
+            struck: vec![],
+            friendly_fire: false,
+        }
+    }
+
+    pub fn with_friendly_fire(mut self, friendly_fire: bool) -> Self {
+        self.friendly_fire = friendly_fire;
+        self
+    }
+
+    // Who the shot and everything it sets off on landing affects
+    fn targets_in(&self, world: &mut World, area: &[Point]) -> Vec<Entity> {
+        find_targets_in_area(world, self.invoker, area, self.friendly_fire)
+    }
+}
+
+pub fn fire_projectile(world: &mut World, appearance: AppearanceKind, target: Point, projectile: Projectile, impact: &[PostMovementActionKind]) -> Entity {
+    let start = find_position(world, projectile.invoker).unwrap_or_else(|| SizedPoint::from(target));
+    world.send_event(SpawnEffectEvent::new(EffectKind::MuzzleFlash, start.visual_center()));
+    launch_projectile(world, start, appearance, target, projectile, impact)
+}
+
+fn launch_projectile(
+    world: &mut World,
+    start: SizedPoint,
+    appearance: AppearanceKind,
+    target: Point,
+    projectile: Projectile,
+    impact: &[PostMovementActionKind],
+) -> Entity {
+    let end = SizedPoint::from(target);
+    let actions = impact.iter().cloned().chain(once(PostMovementActionKind::ResolveProjectile)).collect();
+    let entity = world
+        .spawn()
+        .insert(Position::from(end))
+        .insert(Appearance::new(appearance))
+        .insert(Animation::new())
+        .insert(projectile)
+        .insert(PostMovementAction::sequence(actions))
+        .id();
+    world.send_event(MovementAnimationEvent::new(entity, start.visual_center(), end.visual_center()));
+    entity
+}
+
+pub fn resolve_projectile(world: &mut World, projectile: Entity) {
+    let hit = match world.get_entity_mut(projectile) {
+        Some(mut entity) => entity.remove::<Projectile>(),
+        None => None,
+    };
+    // The shot lingers on screen for a moment after it is gone
+    let appearance = world.get::<Appearance>(projectile).map(|a| a.kind.clone());
+    let landing = find_position(world, projectile);
+    if let (Some(appearance), Some(position)) = (appearance, landing) {
+        world.send_event(SpawnEffectEvent::new(EffectKind::ProjectileFade(appearance), position.visual_center()));
+    }
+    world.despawn(projectile);
+
+    if let Some(hit) = hit {
+        let characters = hit.targets_in(world, &hit.area);
+        if characters.is_empty() {
+            send_combat_event(world, CombatEvent::new(None, landing, CombatEventKind::Miss));
+        }
+        for character in characters {
+            send_hit_spark(world, character);
+            apply_damage(world, character, hit.damage);
+        }
+    }
+}
+
+fn send_hit_spark(world: &mut World, character: Entity) {
+    if let Some(position) = find_position(world, character) {
+        world.send_event(SpawnEffectEvent::new(EffectKind::HitSpark, position.visual_center()));
+    }
+}
+
+// Tiles the landed entity affects, which for a shot is everything it was aimed at
+fn landing_area(world: &World, entity: Entity) -> Vec<Point> {
+    if let Some(projectile) = world.get::<Projectile>(entity) {
+        projectile.area.clone()
+    } else if let Some(position) = world.get::<Position>(entity) {
+        position.position.covered_points()
+    } else {
+        vec![]
+    }
+}
+
+fn find_chain_target(world: &mut World, invoker: Entity, from: SizedPoint, range: u32, struck: &[Entity]) -> Option<(Entity, Point)> {
+    let mut query = world.query_filtered::<(Entity, &Position), With<Character>>();
+    let candidates: Vec<(Entity, SizedPoint)> = query.iter(world).map(|(entity, position)| (entity, position.position)).collect();
+    candidates
+        .into_iter()
+        .filter(|(entity, _)| !struck.contains(entity) && is_hostile(world, invoker, *entity))
+        .filter_map(|(entity, position)| {
+            let target = position.nearest_point_to(from);
+            from.distance_to(target).filter(|d| *d <= range).map(|d| (d, entity, target))
+        })
+        .min_by_key(|(distance, _, _)| *distance)
+        .map(|(_, entity, target)| (entity, target))
+}
+
+// Characters an impact hurts, with anything not fired by a character hitting everyone
+fn impact_targets(world: &mut World, entity: Entity, area: &[Point]) -> Vec<Entity> {
+    match world.get::<Projectile>(entity).cloned() {
+        Some(projectile) => projectile.targets_in(world, area),
+        None => find_characters_in_area(world, area),
+    }
+}
+
+pub fn run_post_movement_action(world: &mut World, entity: Entity, action: &PostMovementActionKind) {
+    // An earlier action may have already removed it
+    let position = match find_position(world, entity) {
+        Some(position) => position,
+        None => return,
+    };
+    let invoker = world.get::<Projectile>(entity).map(|p| p.invoker);
+
+    match action {
+        PostMovementActionKind::Despawn => {
+            world.despawn(entity);
+        }
+        PostMovementActionKind::ResolveProjectile => resolve_projectile(world, entity),
+        PostMovementActionKind::Explode { area, damage } => {
+            world.send_event(SpawnEffectEvent::new(EffectKind::SpellBurst, position.visual_center()));
+            let area = affected_area(position, position.origin, *area);
+            for character in impact_targets(world, entity, &area) {
+                send_hit_spark(world, character);
+                apply_damage(world, character, *damage);
+            }
+        }
+        PostMovementActionKind::SpawnField { kind, area, duration } => {
+            let area = affected_area(position, position.origin, *area);
+            let mut field = Fields::for_kind(*kind, &area);
+            if let Some(duration) = duration {
+                field = field.with_duration(*duration);
+            }
+            if let Some(invoker) = invoker {
+                field = field.with_owner(invoker);
+            }
+            world.spawn().insert(field);
+        }
+        PostMovementActionKind::ApplyEffect { kind, duration, stacks } => {
+            let area = landing_area(world, entity);
+            for character in impact_targets(world, entity, &area) {
+                add_status_effect(world, character, *kind, *duration, *stacks);
+            }
+        }
+        PostMovementActionKind::Chain { range, actions } => {
+            let (invoker, damage, friendly_fire, mut struck) = match world.get::<Projectile>(entity) {
+                Some(projectile) => (projectile.invoker, projectile.damage, projectile.friendly_fire, projectile.struck.clone()),
+                None => return,
+            };
+            let area = landing_area(world, entity);
+            struck.extend(find_characters_in_area(world, &area));
+            if let Some((target_entity, target)) = find_chain_target(world, invoker, position, *range, &struck) {
+                let area = find_position(world, target_entity).unwrap().covered_points();
+                let appearance = world.get::<Appearance>(entity).map(|a| a.kind.clone()).unwrap_or(AppearanceKind::FireBolt);
+                let projectile = Projectile {
+                    struck,
+                    ..Projectile::new(invoker, area, damage).with_friendly_fire(friendly_fire)
+                };
+                launch_projectile(world, position, appearance, target, projectile, actions);
+            }
+        }
+    }
+}
+
+// The turn was already used up when the skill fired, so landing only settles who died
+pub fn run_post_movement_actions(world: &mut World, entity: Entity, actions: &[PostMovementActionKind]) {
+    for action in actions {
+        run_post_movement_action(world, entity, action);
+    }
+    remove_dead(world);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{invoke_skill, Health, Map, MapKind, Skill, SkillEffect, StatusEffects, TargetType, Team};
+
+    use super::*;
+
+    // Lands every shot still in the air, including any they chain into, as the ui does once their animations end
+    fn land_all_projectiles(world: &mut World) {
+        loop {
+            let mut query = world.query_filtered::<(Entity, &PostMovementAction), With<Projectile>>();
+            let landing: Vec<(Entity, Vec<PostMovementActionKind>)> = query.iter(world).map(|(entity, action)| (entity, action.kinds().to_vec())).collect();
+            if landing.is_empty() {
+                break;
+            }
+            for (entity, actions) in landing {
+                run_post_movement_actions(world, entity, &actions);
+            }
+        }
+    }
+
+    fn projectile_test_world() -> (World, Entity) {
+        let mut world = World::new();
+        world.insert_resource(Map::empty(MapKind::Ashlands));
+        let invoker = world.spawn().insert(Character).insert(Team::Player).insert(Position::new(2, 2)).id();
+        (world, invoker)
+    }
+
+    fn spawn_enemy(world: &mut World, x: u32, y: u32) -> Entity {
+        world
+            .spawn()
+            .insert(Character)
+            .insert(Team::Enemy)
+            .insert(Position::new(x, y))
+            .insert(Health::new(10))
+            .insert(StatusEffects::new())
+            .id()
+    }
+
+    #[test]
+    fn damage_waits_for_landing() {
+        let (mut world, invoker) = projectile_test_world();
+        let target = spawn_enemy(&mut world, 6, 2);
+
+        let skill = Skill::new("Test", SkillEffect::RangedAttack, TargetType::Any)
+            .with_damage(3)
+            .with_projectile(AppearanceKind::FireBolt);
+        invoke_skill(&mut world, invoker, &skill, Point::new(6, 2));
+        assert_eq!(10, world.get::<Health>(target).unwrap().current);
+
+        let projectile = world.query_filtered::<Entity, With<Projectile>>().iter(&world).next().unwrap();
+        resolve_projectile(&mut world, projectile);
+        assert_eq!(7, world.get::<Health>(target).unwrap().current);
+        assert!(world.get_entity(projectile).is_none());
+    }
+
+    #[test]
+    fn each_shot_uses_a_turn() {
+        let (mut world, invoker) = projectile_test_world();
+        let target = spawn_enemy(&mut world, 6, 2);
+        // Count the turns that pass, one wearing off after two and the other after three
+        add_status_effect(&mut world, target, StatusEffectKind::Slowed, Some(2), 1);
+        add_status_effect(&mut world, target, StatusEffectKind::Hasted, Some(3), 1);
+        let has = |world: &World, kind| world.get::<StatusEffects>(target).unwrap().has(kind);
+
+        let skill = Skill::new("Test", SkillEffect::RangedAttack, TargetType::Any)
+            .with_damage(3)
+            .with_projectile(AppearanceKind::FireBolt);
+        invoke_skill(&mut world, invoker, &skill, Point::new(6, 2));
+        invoke_skill(&mut world, invoker, &skill, Point::new(6, 2));
+        assert_eq!(2, world.query::<&Projectile>().iter(&world).count());
+        assert!(!has(&world, StatusEffectKind::Slowed));
+        assert!(has(&world, StatusEffectKind::Hasted));
+
+        // Landing does the damage without passing any more turns
+        land_all_projectiles(&mut world);
+        assert_eq!(4, world.get::<Health>(target).unwrap().current);
+        assert!(has(&world, StatusEffectKind::Hasted));
+    }
+
+    #[test]
+    fn resolve_all() {
+        let (mut world, invoker) = projectile_test_world();
+        let target = spawn_enemy(&mut world, 6, 2);
+        fire_projectile(
+            &mut world,
+            AppearanceKind::FireBolt,
+            Point::new(6, 2),
+            Projectile::new(invoker, vec![Point::new(6, 2)], 2),
+            &[],
+        );
+        fire_projectile(
+            &mut world,
+            AppearanceKind::FireBolt,
+            Point::new(6, 2),
+            Projectile::new(invoker, vec![Point::new(6, 2)], 2),
+            &[],
+        );
+
+        land_all_projectiles(&mut world);
+        assert_eq!(6, world.get::<Health>(target).unwrap().current);
+    }
+
+    #[test]
+    fn explode_and_apply_effect() {
+        let (mut world, invoker) = projectile_test_world();
+        let target = spawn_enemy(&mut world, 6, 2);
+        let bystander = spawn_enemy(&mut world, 7, 2);
+        let impact = [
+            PostMovementActionKind::Explode {
+                area: AreaShape::Circle(1),
+                damage: 1,
+            },
+            PostMovementActionKind::ApplyEffect {
+                kind: StatusEffectKind::Slowed,
+                duration: Some(2),
+                stacks: 1,
+            },
+        ];
+        fire_projectile(
+            &mut world,
+            AppearanceKind::FireBolt,
+            Point::new(6, 2),
+            Projectile::new(invoker, vec![Point::new(6, 2)], 2),
+            &impact,
+        );
+
+        land_all_projectiles(&mut world);
+        assert_eq!(7, world.get::<Health>(target).unwrap().current);
+        assert_eq!(9, world.get::<Health>(bystander).unwrap().current);
+        assert!(world.get::<StatusEffects>(target).unwrap().has(StatusEffectKind::Slowed));
+        assert!(!world.get::<StatusEffects>(bystander).unwrap().has(StatusEffectKind::Slowed));
+    }
+
+    #[test]
+    fn explode_spares_allies() {
+        let (mut world, invoker) = projectile_test_world();
+        world.entity_mut(invoker).insert(Health::new(10));
+        let target = spawn_enemy(&mut world, 3, 2);
+        let impact = [PostMovementActionKind::Explode {
+            area: AreaShape::Circle(1),
+            damage: 1,
+        }];
+        fire_projectile(
+            &mut world,
+            AppearanceKind::FireBolt,
+            Point::new(3, 2),
+            Projectile::new(invoker, vec![Point::new(3, 2)], 0),
+            &impact,
+        );
+        land_all_projectiles(&mut world);
+        assert_eq!(9, world.get::<Health>(target).unwrap().current);
+        assert_eq!(10, world.get::<Health>(invoker).unwrap().current);
+
+        let shot = Projectile::new(invoker, vec![Point::new(3, 2)], 0).with_friendly_fire(true);
+        fire_projectile(&mut world, AppearanceKind::FireBolt, Point::new(3, 2), shot, &impact);
+        land_all_projectiles(&mut world);
+        assert_eq!(8, world.get::<Health>(target).unwrap().current);
+        assert_eq!(9, world.get::<Health>(invoker).unwrap().current);
+    }
+
+    #[test]
+    fn spawn_field() {
+        let (mut world, invoker) = projectile_test_world();
+        let impact = [PostMovementActionKind::SpawnField {
+            kind: FieldKind::Fire,
+            area: AreaShape::Single,
+            duration: Some(3),
+        }];
+        fire_projectile(
+            &mut world,
+            AppearanceKind::FireBolt,
+            Point::new(6, 2),
+            Projectile::new(invoker, vec![Point::new(6, 2)], 0),
+            &impact,
+        );
+
+        land_all_projectiles(&mut world);
+        let field = world.query::<&Fields>().iter(&world).next().unwrap();
+        assert_eq!(vec![Point::new(6, 2)], field.positions);
+        assert_eq!(Some(invoker), field.owner);
+    }
+
+    #[test]
+    fn chain_to_nearest_hostile() {
+        let (mut world, invoker) = projectile_test_world();
+        let first = spawn_enemy(&mut world, 6, 2);
+        let second = spawn_enemy(&mut world, 8, 2);
+        let far = spawn_enemy(&mut world, 12, 2);
+        let impact = [PostMovementActionKind::Chain {
+            range: 3,
+            actions: vec![PostMovementActionKind::Chain { range: 3, actions: vec![] }],
+        }];
+        fire_projectile(
+            &mut world,
+            AppearanceKind::FireBolt,
+            Point::new(6, 2),
+            Projectile::new(invoker, vec![Point::new(6, 2)], 2),
+            &impact,
+        );
+
+        land_all_projectiles(&mut world);
+        assert_eq!(8, world.get::<Health>(first).unwrap().current);
+        assert_eq!(8, world.get::<Health>(second).unwrap().current);
+        // Out of range of the second bounce
+        assert_eq!(10, world.get::<Health>(far).unwrap().current);
+        assert_eq!(0, world.query::<&Projectile>().iter(&world).count());
+    }
+}
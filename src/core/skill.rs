@@ -3,13 +3,36 @@ use std::slice::from_ref;
 use bevy_ecs::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use super::{find_character_at_location, find_position, is_area_clear_of_others, is_player_or_ally, Point};
+use super::{
+    affected_area, apply_damage, blocks_vision, can_act, end_turn, face_towards, find_character_at_location, find_forced_movement_target, find_position,
+    find_targets_in_area, fire_projectile, is_allied, is_area_clear_of_others, is_hostile, movement_modifier, pull, push, step_towards, summon,
+    summon_footprint, swap, AnimationState, AppearanceKind, AreaShape, NewMessageEvent, Point, PostMovementActionKind, Projectile, SizedPoint,
+};
+use crate::ui::SpriteAnimateActionEvent;
 
-#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum SkillEffect {
     None,
     Move,
     RangedAttack,
+    // Forced movement of the targeted character by up to the given distance
+    Push(u32),
+    Pull(u32),
+    Swap,
+    // Creates a character from the named template on the invoker's team
+    Summon { template: String, lifetime: u32 },
+}
+
+impl SkillEffect {
+    fn default_animation(&self) -> Option<AnimationState> {
+        match self {
+            SkillEffect::None | SkillEffect::Move => None,
+            SkillEffect::RangedAttack => Some(AnimationState::Bow),
+            SkillEffect::Push(_) | SkillEffect::Pull(_) | SkillEffect::Swap => Some(AnimationState::AttackOne),
+            SkillEffect::Summon { .. } => Some(AnimationState::Magic),
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -17,7 +40,8 @@ pub enum SkillEffect {
 pub enum TargetType {
     None,
     Tile,
-    Player,
+    // The invoker or anyone on a friendly team
+    Ally,
     Enemy,
     Any,
     AnyoneButSelf,
@@ -30,16 +54,35 @@ pub struct Skill {
     pub target: TargetType,
     pub range: Option<u32>,
     pub path_must_be_clear: bool,
+    pub area: AreaShape,
+    pub damage: u32,
+    // Shown flying to the target, with damage held back until it lands
+    pub projectile: Option<AppearanceKind>,
+    // Run in order where the projectile lands
+    #[serde(default)]
+    pub on_impact: Vec<PostMovementActionKind>,
+    // Played by the invoker as the skill goes off
+    #[serde(default)]
+    pub animation: Option<AnimationState>,
+    // Area damage also hurts allies and the invoker caught in it
+    #[serde(default)]
+    pub friendly_fire: bool,
 }
 
 impl Skill {
     pub fn new(name: &str, kind: SkillEffect, target: TargetType) -> Self {
         Skill {
             name: name.to_string(),
-            kind,
             target,
             range: None,
             path_must_be_clear: false,
+            area: AreaShape::Single,
+            damage: 0,
+            projectile: None,
+            on_impact: vec![],
+            animation: kind.default_animation(),
+            friendly_fire: false,
+            kind,
         }
     }
 
@@ -53,6 +96,38 @@ impl Skill {
         self
     }
 
+    pub fn with_area(mut self, area: AreaShape) -> Skill {
+        self.area = area;
+        self
+    }
+
+    pub fn with_damage(mut self, damage: u32) -> Skill {
+        self.damage = damage;
+        self
+    }
+
+    pub fn with_projectile(mut self, projectile: AppearanceKind) -> Skill {
+        self.projectile = Some(projectile);
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_animation(mut self, animation: AnimationState) -> Skill {
+        self.animation = Some(animation);
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_friendly_fire(mut self) -> Skill {
+        self.friendly_fire = true;
+        self
+    }
+
+    pub fn with_impact(mut self, action: PostMovementActionKind) -> Skill {
+        self.on_impact.push(action);
+        self
+    }
+
     pub fn show_trail(&self) -> bool {
         self.path_must_be_clear
     }
@@ -64,17 +139,24 @@ pub fn is_valid_target(world: &mut World, invoker: Entity, skill: &Skill, target
     }
 
     let final_point_good = match skill.target {
-        TargetType::Tile => is_area_clear_of_others(world, from_ref(&target), None),
+        TargetType::Tile => match &skill.kind {
+            // A large summon needs room for all of itself, not just the tile clicked
+            SkillEffect::Summon { template, .. } => match summon_footprint(world, template, target) {
+                Some(footprint) => is_area_clear_of_others(world, &footprint.covered_points(), None),
+                None => false,
+            },
+            _ => is_area_clear_of_others(world, from_ref(&target), None),
+        },
         TargetType::Enemy => {
             if let Some(potential_target) = find_character_at_location(world, target) {
-                !is_player_or_ally(world, potential_target)
+                is_hostile(world, invoker, potential_target)
             } else {
                 false
             }
         }
-        TargetType::Player => {
+        TargetType::Ally => {
             if let Some(potential_target) = find_character_at_location(world, target) {
-                is_player_or_ally(world, potential_target)
+                is_allied(world, invoker, potential_target)
             } else {
                 false
             }
@@ -101,8 +183,17 @@ pub fn is_valid_target(world: &mut World, invoker: Entity, skill: &Skill, target
     true
 }
 
+pub fn skill_range(world: &World, invoker: Entity, skill: &Skill) -> Option<u32> {
+    match skill.kind {
+        SkillEffect::Move => skill
+            .range
+            .map(|range| std::cmp::max(range as i32 + movement_modifier(world, invoker), 0) as u32),
+        _ => skill.range,
+    }
+}
+
 pub fn in_possible_skill_range(world: &mut World, invoker: Entity, skill: &Skill, target: Point) -> bool {
-    if let Some(skill_range) = skill.range {
+    if let Some(skill_range) = skill_range(world, invoker, skill) {
         if let Some(range_to_target) = find_position(world, invoker).unwrap().distance_to(target) {
             if range_to_target > skill_range {
                 return false;
@@ -113,7 +204,7 @@ pub fn in_possible_skill_range(world: &mut World, invoker: Entity, skill: &Skill
     if skill.path_must_be_clear {
         if let Some(mut path) = find_position(world, invoker).unwrap().line_to(target) {
             path.pop();
-            if !is_area_clear_of_others(world, &path, Some(invoker)) {
+            if !is_area_clear_of_others(world, &path, Some(invoker)) || blocks_vision(world, &path) {
                 return false;
             }
         }
@@ -121,6 +212,75 @@ pub fn in_possible_skill_range(world: &mut World, invoker: Entity, skill: &Skill
     true
 }
 
+pub fn skill_area(world: &mut World, invoker: Entity, skill: &Skill, target: Point) -> Vec<Point> {
+    match find_position(world, invoker) {
+        Some(position) => affected_area(position, target, skill.area),
+        None => vec![target],
+    }
+}
+
+pub fn invoke_skill(world: &mut World, invoker: Entity, skill: &Skill, target: Point) {
+    // Like moving, trying to act while stunned passes the turn
+    if !can_act(world, invoker) {
+        world.send_event(NewMessageEvent::new("Stunned!"));
+        end_turn(world);
+        return;
+    }
+
+    face_towards(world, invoker, SizedPoint::from(target));
+    if let Some(animation) = skill.animation {
+        world.send_event(SpriteAnimateActionEvent::new(invoker, animation));
+    }
+
+    // Whether the skill went off and so used up the turn, even if its shots are still in the air
+    let ends_turn = match &skill.kind {
+        SkillEffect::None => true,
+        // Not getting anywhere at all does not cost the turn
+        SkillEffect::Move => step_towards(world, invoker, target) > 0,
+        SkillEffect::RangedAttack => {
+            let area = skill_area(world, invoker, skill, target);
+            if let Some(projectile) = &skill.projectile {
+                let shot = Projectile::new(invoker, area, skill.damage).with_friendly_fire(skill.friendly_fire);
+                fire_projectile(world, projectile.clone(), target, shot, &skill.on_impact);
+            } else {
+                for character in find_targets_in_area(world, invoker, &area, skill.friendly_fire) {
+                    apply_damage(world, character, skill.damage);
+                }
+            }
+            true
+        }
+        SkillEffect::Push(distance) => {
+            if let Some(target) = find_forced_movement_target(world, invoker, target) {
+                if skill.damage > 0 {
+                    apply_damage(world, target, skill.damage);
+                }
+                push(world, invoker, target, *distance);
+            }
+            true
+        }
+        SkillEffect::Pull(distance) => {
+            if let Some(target) = find_forced_movement_target(world, invoker, target) {
+                if skill.damage > 0 {
+                    apply_damage(world, target, skill.damage);
+                }
+                pull(world, invoker, target, *distance);
+            }
+            true
+        }
+        SkillEffect::Swap => {
+            if let Some(target) = find_forced_movement_target(world, invoker, target) {
+                swap(world, invoker, target);
+            }
+            true
+        }
+        // Nothing happened, so the turn is not lost
+        SkillEffect::Summon { template, lifetime } => summon(world, invoker, template, target, *lifetime).is_some(),
+    };
+    if ends_turn {
+        end_turn(world);
+    }
+}
+
 #[derive(Component, Debug, Deserialize, Serialize)]
 pub struct Skills {
     pub skills: Vec<Skill>,
@@ -134,14 +294,24 @@ impl Skills {
 
 #[cfg(test)]
 mod tests {
-    use crate::core::{Character, Map, MapKind, Player, Position};
+    use crate::core::{
+        add_status_effect, Character, FieldColor, FieldEffect, Fields, Health, Map, MapKind, Player, Position, StatusEffectKind, StatusEffects,
+        SummonTemplates, Team,
+    };
+    use crate::ui::MovementAnimationEvent;
 
     use super::*;
 
     fn skill_test_world() -> (World, Entity) {
         let mut world = World::new();
-        let first = world.spawn().insert(Character).insert(Player).insert(Position::new(2, 2)).id();
-        world.spawn().insert(Character).insert(Position::new(3, 3));
+        let first = world
+            .spawn()
+            .insert(Character)
+            .insert(Player)
+            .insert(Team::Player)
+            .insert(Position::new(2, 2))
+            .id();
+        world.spawn().insert(Character).insert(Team::Enemy).insert(Position::new(3, 3));
         world.insert_resource(Map::empty(MapKind::Ashlands));
         (world, first)
     }
@@ -168,8 +338,8 @@ mod tests {
     }
 
     #[test]
-    fn valid_target_player() {
-        let skill = Skill::new("Test", SkillEffect::None, TargetType::Player);
+    fn valid_target_ally() {
+        let skill = Skill::new("Test", SkillEffect::None, TargetType::Ally);
         let (mut world, first) = skill_test_world();
 
         assert!(is_valid_target(&mut world, first, &skill, Point::new(2, 2)));
@@ -230,4 +400,184 @@ mod tests {
         assert!(is_valid_target(&mut world, first, &skill, Point::new(3, 3)));
         assert!(!is_valid_target(&mut world, first, &skill, Point::new(4, 4)));
     }
+
+    #[test]
+    fn ranged_attack_hits_area_once() {
+        let skill = Skill::new("Test", SkillEffect::RangedAttack, TargetType::Any)
+            .with_area(AreaShape::Circle(1))
+            .with_damage(2);
+        let (mut world, first) = skill_test_world();
+        let golem = world
+            .spawn()
+            .insert(Character)
+            .insert(Position::new_sized(5, 5, 2, 2))
+            .insert(Health::new(10))
+            .id();
+
+        invoke_skill(&mut world, first, &skill, Point::new(5, 5));
+        assert_eq!(8, world.get::<Health>(golem).unwrap().current);
+    }
+
+    #[test]
+    fn area_attack_spares_allies() {
+        let skill = Skill::new("Test", SkillEffect::RangedAttack, TargetType::Any)
+            .with_area(AreaShape::Circle(1))
+            .with_damage(2);
+        let (mut world, first) = skill_test_world();
+        world.entity_mut(first).insert(Health::new(10));
+        let ally = world
+            .spawn()
+            .insert(Character)
+            .insert(Team::Player)
+            .insert(Position::new(2, 3))
+            .insert(Health::new(10))
+            .id();
+        let enemy = find_character_at_location(&mut world, Point::new(3, 3)).unwrap();
+        world.entity_mut(enemy).insert(Health::new(10));
+
+        invoke_skill(&mut world, first, &skill, Point::new(2, 3));
+        assert_eq!(10, world.get::<Health>(first).unwrap().current);
+        assert_eq!(10, world.get::<Health>(ally).unwrap().current);
+        assert_eq!(8, world.get::<Health>(enemy).unwrap().current);
+
+        invoke_skill(&mut world, first, &skill.with_friendly_fire(), Point::new(2, 3));
+        assert_eq!(8, world.get::<Health>(first).unwrap().current);
+        assert_eq!(8, world.get::<Health>(ally).unwrap().current);
+        assert_eq!(6, world.get::<Health>(enemy).unwrap().current);
+    }
+
+    #[test]
+    fn move_skill() {
+        let skill = Skill::new("Test", SkillEffect::Move, TargetType::Tile);
+        let (mut world, first) = skill_test_world();
+
+        invoke_skill(&mut world, first, &skill, Point::new(2, 4));
+        assert_eq!(Point::new(2, 4), find_position(&mut world, first).unwrap().origin);
+    }
+
+    #[test]
+    fn move_skill_checks_each_step() {
+        let skill = Skill::new("Test", SkillEffect::Move, TargetType::Tile);
+        let (mut world, first) = skill_test_world();
+        world.get_resource_mut::<Map>().unwrap().set_walkable(&Point::new(4, 1), false);
+
+        // Can not dodge through the enemy
+        invoke_skill(&mut world, first, &skill, Point::new(4, 4));
+        assert_eq!(Point::new(2, 2), find_position(&mut world, first).unwrap().origin);
+
+        // Stops short at the wall
+        invoke_skill(&mut world, first, &skill, Point::new(4, 0));
+        assert_eq!(Point::new(3, 1), find_position(&mut world, first).unwrap().origin);
+    }
+
+    #[test]
+    fn hasted_movement_range() {
+        let skill = Skill::new("Test", SkillEffect::Move, TargetType::Tile).with_range(2);
+        let (mut world, first) = skill_test_world();
+
+        assert!(!is_valid_target(&mut world, first, &skill, Point::new(2, 5)));
+        add_status_effect(&mut world, first, StatusEffectKind::Hasted, Some(2), 1);
+        assert!(is_valid_target(&mut world, first, &skill, Point::new(2, 5)));
+    }
+
+    #[test]
+    fn slowed_movement_range() {
+        let skill = Skill::new("Test", SkillEffect::Move, TargetType::Tile).with_range(2);
+        let (mut world, first) = skill_test_world();
+
+        assert!(is_valid_target(&mut world, first, &skill, Point::new(2, 4)));
+        add_status_effect(&mut world, first, StatusEffectKind::Slowed, Some(2), 1);
+        assert!(!is_valid_target(&mut world, first, &skill, Point::new(2, 4)));
+    }
+
+    #[test]
+    fn clear_path_blocked_by_vision() {
+        let skill = Skill::new("Test", SkillEffect::None, TargetType::Any).with_range(3).path_must_be_clear();
+        let (mut world, first) = skill_test_world();
+        world
+            .spawn()
+            .insert(Fields::new(FieldColor::Black, &[Point::new(2, 3)]).with_effect(FieldEffect::BlockVision));
+
+        assert!(is_valid_target(&mut world, first, &skill, Point::new(1, 2)));
+        assert!(!is_valid_target(&mut world, first, &skill, Point::new(2, 4)));
+    }
+
+    #[test]
+    fn shove_kills_into_wall() {
+        let skill = Skill::new("Shove", SkillEffect::Push(2), TargetType::AnyoneButSelf).with_range(1);
+        let (mut world, first) = skill_test_world();
+        world.insert_resource(Events::<MovementAnimationEvent>::default());
+        let target = find_character_at_location(&mut world, Point::new(3, 3)).unwrap();
+        world.get_mut::<Position>(target).unwrap().position = SizedPoint::new(3, 2);
+        world.entity_mut(target).insert(Health::new(2));
+        world.get_resource_mut::<Map>().unwrap().set_walkable(&Point::new(5, 2), false);
+
+        invoke_skill(&mut world, first, &skill, Point::new(3, 2));
+        assert!(world.get_entity(target).is_none());
+
+        // The ui is still sent the movement for an entity that no longer exists, and must skip it
+        let events = world.get_resource::<Events<MovementAnimationEvent>>().unwrap();
+        let mut reader = events.get_reader();
+        assert!(reader.iter(events).any(|e| e.entity == target));
+    }
+
+    #[test]
+    fn summon_target_needs_room_for_footprint() {
+        let skill = Skill::new(
+            "Summon",
+            SkillEffect::Summon {
+                template: "Golem".to_string(),
+                lifetime: 3,
+            },
+            TargetType::Tile,
+        )
+        .with_range(5);
+        let (mut world, first) = skill_test_world();
+        world.insert_resource(SummonTemplates::new());
+
+        // The enemy at (3,3) is in the way of a golem at (2,3) but not at (4,3)
+        assert!(!is_valid_target(&mut world, first, &skill, Point::new(2, 3)));
+        assert!(is_valid_target(&mut world, first, &skill, Point::new(4, 3)));
+        world.get_resource_mut::<Map>().unwrap().set_walkable(&Point::new(5, 4), false);
+        assert!(!is_valid_target(&mut world, first, &skill, Point::new(4, 3)));
+    }
+
+    #[test]
+    fn failed_summon_keeps_turn() {
+        let skill = Skill::new(
+            "Summon",
+            SkillEffect::Summon {
+                template: "Golem".to_string(),
+                lifetime: 3,
+            },
+            TargetType::Tile,
+        );
+        let (mut world, first) = skill_test_world();
+        world.insert_resource(SummonTemplates::new());
+        world.entity_mut(first).insert(StatusEffects::new());
+        add_status_effect(&mut world, first, StatusEffectKind::Hasted, Some(1), 1);
+
+        invoke_skill(&mut world, first, &skill, Point::new(2, 3));
+        assert!(world.get::<StatusEffects>(first).unwrap().has(StatusEffectKind::Hasted));
+
+        invoke_skill(&mut world, first, &skill, Point::new(5, 5));
+        assert!(!world.get::<StatusEffects>(first).unwrap().has(StatusEffectKind::Hasted));
+    }
+
+    #[test]
+    fn stunned_cannot_use_skills() {
+        let skill = Skill::new("Test", SkillEffect::RangedAttack, TargetType::Enemy).with_damage(3);
+        let (mut world, first) = skill_test_world();
+        let target = find_character_at_location(&mut world, Point::new(3, 3)).unwrap();
+        world.entity_mut(target).insert(Health::new(10));
+        world.entity_mut(first).insert(StatusEffects::new());
+        add_status_effect(&mut world, first, StatusEffectKind::Stunned, Some(1), 1);
+
+        invoke_skill(&mut world, first, &skill, Point::new(3, 3));
+        assert_eq!(10, world.get::<Health>(target).unwrap().current);
+
+        // The stun wore off as the turn passed
+        invoke_skill(&mut world, first, &skill, Point::new(3, 3));
+        assert_eq!(7, world.get::<Health>(target).unwrap().current);
+    }
 }
@@ -0,0 +1,271 @@
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{apply_damage, find_position, send_combat_event, CombatEvent, CombatEventKind, NewMessageEvent};
+
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum StatusEffectKind {
+    Burning,
+    Stunned,
+    Hasted,
+    Shielded,
+    Slowed,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum StackingRule {
+    // Reapplying keeps a single stack and takes the longer duration
+    Refresh,
+    // Reapplying adds stacks up to the limit and takes the longer duration
+    Stack(u32),
+    // Reapplying while active does nothing
+    Ignore,
+}
+
+impl StatusEffectKind {
+    pub fn stacking(&self) -> StackingRule {
+        match self {
+            StatusEffectKind::Burning => StackingRule::Stack(5),
+            StatusEffectKind::Stunned => StackingRule::Ignore,
+            StatusEffectKind::Hasted => StackingRule::Refresh,
+            // Each stack of shield absorbs one point of damage
+            StatusEffectKind::Shielded => StackingRule::Stack(20),
+            StatusEffectKind::Slowed => StackingRule::Refresh,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            StatusEffectKind::Burning => "Burning",
+            StatusEffectKind::Stunned => "Stunned",
+            StatusEffectKind::Hasted => "Hasted",
+            StatusEffectKind::Shielded => "Shielded",
+            StatusEffectKind::Slowed => "Slowed",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    // None lasts until removed
+    pub duration: Option<u32>,
+    pub stacks: u32,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusEffectKind, duration: Option<u32>, stacks: u32) -> Self {
+        StatusEffect { kind, duration, stacks }
+    }
+}
+
+fn longer_duration(first: Option<u32>, second: Option<u32>) -> Option<u32> {
+    match (first, second) {
+        (Some(first), Some(second)) => Some(std::cmp::max(first, second)),
+        _ => None,
+    }
+}
+
+#[derive(Component, Debug, Deserialize, Serialize)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn new() -> Self {
+        StatusEffects { effects: vec![] }
+    }
+
+    pub fn add(&mut self, kind: StatusEffectKind, duration: Option<u32>, stacks: u32) {
+        if let Some(existing) = self.effects.iter_mut().find(|e| e.kind == kind) {
+            match kind.stacking() {
+                StackingRule::Refresh => {
+                    existing.duration = longer_duration(existing.duration, duration);
+                }
+                StackingRule::Stack(max) => {
+                    existing.duration = longer_duration(existing.duration, duration);
+                    existing.stacks = std::cmp::min(existing.stacks + stacks, max);
+                }
+                StackingRule::Ignore => {}
+            }
+        } else {
+            let stacks = match kind.stacking() {
+                StackingRule::Stack(max) => std::cmp::min(stacks, max),
+                _ => 1,
+            };
+            self.effects.push(StatusEffect::new(kind, duration, stacks));
+        }
+    }
+
+    pub fn remove(&mut self, kind: StatusEffectKind) {
+        self.effects.retain(|e| e.kind != kind);
+    }
+
+    pub fn has(&self, kind: StatusEffectKind) -> bool {
+        self.effects.iter().any(|e| e.kind == kind)
+    }
+
+    pub fn stacks(&self, kind: StatusEffectKind) -> u32 {
+        self.effects.iter().find(|e| e.kind == kind).map(|e| e.stacks).unwrap_or(0)
+    }
+
+    // Counts down every effect with a duration, returning the kinds that expired
+    pub fn tick(&mut self) -> Vec<StatusEffectKind> {
+        for effect in self.effects.iter_mut() {
+            if let Some(duration) = &mut effect.duration {
+                *duration = duration.saturating_sub(1);
+            }
+        }
+        let expired = self.effects.iter().filter(|e| e.duration == Some(0)).map(|e| e.kind).collect();
+        self.effects.retain(|e| e.duration != Some(0));
+        expired
+    }
+
+    // Returns how much of the damage the shield soaked up
+    pub fn absorb(&mut self, amount: u32) -> u32 {
+        let shield = self.stacks(StatusEffectKind::Shielded);
+        let absorbed = std::cmp::min(shield, amount);
+        if absorbed > 0 {
+            if absorbed == shield {
+                self.remove(StatusEffectKind::Shielded);
+            } else if let Some(effect) = self.effects.iter_mut().find(|e| e.kind == StatusEffectKind::Shielded) {
+                effect.stacks -= absorbed;
+            }
+        }
+        absorbed
+    }
+
+    pub fn can_act(&self) -> bool {
+        !self.has(StatusEffectKind::Stunned)
+    }
+
+    pub fn movement_modifier(&self) -> i32 {
+        let mut modifier = 0;
+        if self.has(StatusEffectKind::Hasted) {
+            modifier += 1;
+        }
+        if self.has(StatusEffectKind::Slowed) {
+            modifier -= 1;
+        }
+        modifier
+    }
+}
+
+pub fn can_act(world: &World, entity: Entity) -> bool {
+    world.get::<StatusEffects>(entity).map(|e| e.can_act()).unwrap_or(true)
+}
+
+pub fn movement_modifier(world: &World, entity: Entity) -> i32 {
+    world.get::<StatusEffects>(entity).map(|e| e.movement_modifier()).unwrap_or(0)
+}
+
+pub fn add_status_effect(world: &mut World, entity: Entity, kind: StatusEffectKind, duration: Option<u32>, stacks: u32) {
+    let added = {
+        let mut entity = world.entity_mut(entity);
+        if !entity.contains::<StatusEffects>() {
+            entity.insert(StatusEffects::new());
+        }
+        let mut effects = entity.get_mut::<StatusEffects>().unwrap();
+        let was_active = effects.has(kind);
+        effects.add(kind, duration, stacks);
+        !was_active
+    };
+    if added {
+        let position = find_position(world, entity);
+        send_combat_event(world, CombatEvent::new(Some(entity), position, CombatEventKind::StatusApplied(kind)));
+    }
+}
+
+pub fn tick_status_effects(world: &mut World) {
+    let mut burning = vec![];
+    let mut expired = vec![];
+    let mut query = world.query::<(Entity, &mut StatusEffects)>();
+    for (entity, mut effects) in query.iter_mut(world) {
+        let burn = effects.stacks(StatusEffectKind::Burning);
+        if burn > 0 {
+            burning.push((entity, burn));
+        }
+        expired.extend(effects.tick());
+    }
+
+    for (entity, burn) in burning {
+        apply_damage(world, entity, burn);
+    }
+    for kind in expired {
+        world.send_event(NewMessageEvent::new(&format!("{} wore off.", kind.name())));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::Health;
+
+    use super::*;
+
+    #[test]
+    fn refresh_takes_longer_duration() {
+        let mut effects = StatusEffects::new();
+        effects.add(StatusEffectKind::Hasted, Some(3), 1);
+        effects.add(StatusEffectKind::Hasted, Some(5), 1);
+        effects.add(StatusEffectKind::Hasted, Some(2), 1);
+        assert_eq!(1, effects.effects.len());
+        assert_eq!(Some(5), effects.effects[0].duration);
+        assert_eq!(1, effects.stacks(StatusEffectKind::Hasted));
+    }
+
+    #[test]
+    fn stacking_is_capped() {
+        let mut effects = StatusEffects::new();
+        effects.add(StatusEffectKind::Burning, Some(3), 2);
+        effects.add(StatusEffectKind::Burning, Some(3), 2);
+        assert_eq!(4, effects.stacks(StatusEffectKind::Burning));
+        effects.add(StatusEffectKind::Burning, Some(3), 2);
+        assert_eq!(5, effects.stacks(StatusEffectKind::Burning));
+    }
+
+    #[test]
+    fn ignore_keeps_original() {
+        let mut effects = StatusEffects::new();
+        effects.add(StatusEffectKind::Stunned, Some(1), 1);
+        effects.add(StatusEffectKind::Stunned, Some(4), 1);
+        assert_eq!(Some(1), effects.effects[0].duration);
+        assert!(!effects.can_act());
+    }
+
+    #[test]
+    fn tick_expires() {
+        let mut effects = StatusEffects::new();
+        effects.add(StatusEffectKind::Stunned, Some(1), 1);
+        effects.add(StatusEffectKind::Hasted, Some(2), 1);
+        effects.add(StatusEffectKind::Shielded, None, 3);
+        assert_eq!(vec![StatusEffectKind::Stunned], effects.tick());
+        assert_eq!(vec![StatusEffectKind::Hasted], effects.tick());
+        assert!(effects.tick().is_empty());
+        assert!(effects.has(StatusEffectKind::Shielded));
+    }
+
+    #[test]
+    fn shield_absorbs() {
+        let mut effects = StatusEffects::new();
+        effects.add(StatusEffectKind::Shielded, None, 3);
+        assert_eq!(2, effects.absorb(2));
+        assert_eq!(1, effects.stacks(StatusEffectKind::Shielded));
+        assert_eq!(1, effects.absorb(4));
+        assert!(!effects.has(StatusEffectKind::Shielded));
+        assert_eq!(0, effects.absorb(4));
+    }
+
+    #[test]
+    fn burning_damages_on_tick() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Health::new(10)).id();
+        add_status_effect(&mut world, entity, StatusEffectKind::Burning, Some(2), 2);
+
+        tick_status_effects(&mut world);
+        assert_eq!(8, world.get::<Health>(entity).unwrap().current);
+        tick_status_effects(&mut world);
+        assert_eq!(6, world.get::<Health>(entity).unwrap().current);
+        tick_status_effects(&mut world);
+        assert_eq!(6, world.get::<Health>(entity).unwrap().current);
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    find_facing, find_team, is_area_clear_of_others, AnimationState, Appearance, AppearanceKind, Character, Direction, Facing, Health, NewMessageEvent, Point,
+    Position, SizedPoint, Skill, Skills, StatusEffects,
+};
+use crate::ui::{EffectKind, SpawnEffectEvent, SpriteAnimateActionEvent};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SummonTemplate {
+    pub name: String,
+    pub appearance: AppearanceKind,
+    pub width: u32,
+    pub height: u32,
+    pub health: u32,
+    pub skills: Vec<Skill>,
+}
+
+impl SummonTemplate {
+    pub fn new(name: &str, appearance: AppearanceKind, health: u32) -> Self {
+        SummonTemplate {
+            name: name.to_string(),
+            appearance,
+            width: 1,
+            height: 1,
+            health,
+            skills: vec![],
+        }
+    }
+
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_skills(mut self, skills: &[Skill]) -> Self {
+        self.skills = Vec::from(skills);
+        self
+    }
+}
+
+pub struct SummonTemplates {
+    templates: HashMap<String, SummonTemplate>,
+}
+
+impl SummonTemplates {
+    pub fn new() -> Self {
+        let mut templates = SummonTemplates { templates: HashMap::new() };
+        templates.add(SummonTemplate::new("Golem", AppearanceKind::Golem, 15).with_size(2, 2));
+        templates
+    }
+
+    pub fn add(&mut self, template: SummonTemplate) {
+        self.templates.insert(template.name.clone(), template);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SummonTemplate> {
+        self.templates.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SummonTemplate> {
+        self.templates.values()
+    }
+}
+
+// Summoned characters disappear once their time runs out
+#[derive(Component, Debug, Deserialize, Serialize)]
+pub struct Lifetime {
+    pub turns: u32,
+}
+
+impl Lifetime {
+    pub fn new(turns: u32) -> Self {
+        Lifetime { turns }
+    }
+}
+
+// Everything the template would cover if summoned at the target, or None for an unknown template
+pub fn summon_footprint(world: &World, template: &str, target: Point) -> Option<SizedPoint> {
+    world
+        .get_resource::<SummonTemplates>()
+        .and_then(|t| t.get(template))
+        .map(|t| SizedPoint::new_sized(target.x, target.y, t.width, t.height))
+}
+
+pub fn summon(world: &mut World, invoker: Entity, template: &str, target: Point, lifetime: u32) -> Option<Entity> {
+    let template = match world.get_resource::<SummonTemplates>().and_then(|t| t.get(template)) {
+        Some(template) => template.clone(),
+        None => {
+            world.send_event(NewMessageEvent::new(&format!("Unknown summon {}.", template)));
+            return None;
+        }
+    };
+
+    let position = SizedPoint::new_sized(target.x, target.y, template.width, template.height);
+    if !is_area_clear_of_others(world, &position.covered_points(), None) {
+        world.send_event(NewMessageEvent::new("No room to summon."));
+        return None;
+    }
+
+    let team = find_team(world, invoker);
+    let facing = find_facing(world, invoker).unwrap_or(Direction::South);
+    let summoned = world
+        .spawn()
+        .insert(Character)
+        .insert(Position::from(position))
+        .insert(Appearance::new(template.appearance))
+        .insert(crate::ui::Animation::new())
+        .insert(Health::new(template.health))
+        .insert(StatusEffects::new())
+        .insert(Skills::new(&template.skills))
+        .insert(team)
+        .insert(Facing::new(facing))
+        .insert(Lifetime::new(lifetime))
+        .id();
+
+    world.send_event(SpawnEffectEvent::new(EffectKind::SpellBurst, position.visual_center()));
+    world.send_event(SpriteAnimateActionEvent::new(summoned, AnimationState::Magic));
+    world.send_event(NewMessageEvent::new(&format!("{} summoned.", template.name)));
+    Some(summoned)
+}
+
+pub fn tick_lifetimes(world: &mut World) {
+    let mut expired = vec![];
+    let mut query = world.query::<(Entity, &mut Lifetime)>();
+    for (entity, mut lifetime) in query.iter_mut(world) {
+        lifetime.turns = lifetime.turns.saturating_sub(1);
+        if lifetime.turns == 0 {
+            expired.push(entity);
+        }
+    }
+    for entity in expired {
+        world.despawn(entity);
+        world.send_event(NewMessageEvent::new("A summon fades away."));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{end_turn, find_character_at_location, is_allied, Map, MapKind, Team};
+
+    use super::*;
+
+    fn summon_test_world() -> (World, Entity) {
+        let mut world = World::new();
+        let invoker = world.spawn().insert(Character).insert(Position::new(2, 2)).insert(Team::Player).id();
+        world.insert_resource(Map::empty(MapKind::Ashlands));
+        world.insert_resource(SummonTemplates::new());
+        (world, invoker)
+    }
+
+    #[test]
+    fn summons_join_invoker_team() {
+        let (mut world, invoker) = summon_test_world();
+        let summoned = summon(&mut world, invoker, "Golem", Point::new(5, 5), 3).unwrap();
+        assert!(is_allied(&world, invoker, summoned));
+        assert_eq!(Some(summoned), find_character_at_location(&mut world, Point::new(6, 6)));
+    }
+
+    #[test]
+    fn summon_needs_room_for_footprint() {
+        let (mut world, invoker) = summon_test_world();
+        world.spawn().insert(Character).insert(Position::new(6, 6));
+        assert!(summon(&mut world, invoker, "Golem", Point::new(5, 5), 3).is_none());
+        assert!(summon(&mut world, invoker, "Golem", Point::new(1, 1), 3).is_none());
+        assert!(summon(&mut world, invoker, "Golem", Point::new(12, 5), 3).is_none());
+        assert!(summon(&mut world, invoker, "Missing", Point::new(8, 8), 3).is_none());
+    }
+
+    #[test]
+    fn summons_expire() {
+        let (mut world, invoker) = summon_test_world();
+        let summoned = summon(&mut world, invoker, "Golem", Point::new(5, 5), 2).unwrap();
+        end_turn(&mut world);
+        assert!(world.get_entity(summoned).is_some());
+        end_turn(&mut world);
+        assert!(world.get_entity(summoned).is_none());
+    }
+}
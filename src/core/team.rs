@@ -0,0 +1,129 @@
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[allow(dead_code)]
+#[derive(Component, Hash, PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
+pub enum Team {
+    Player,
+    Enemy,
+    // Fights no one and is fought by no one unless a relation says otherwise
+    Neutral,
+    Faction(String),
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum Relation {
+    Allied,
+    Hostile,
+    Indifferent,
+}
+
+impl Team {
+    pub fn default_relation(&self, other: &Team) -> Relation {
+        match (self, other) {
+            (first, second) if first == second => Relation::Allied,
+            (Team::Neutral, _) | (_, Team::Neutral) => Relation::Indifferent,
+            _ => Relation::Hostile,
+        }
+    }
+}
+
+// Overrides to the default relations between teams, such as an escorted caravan
+// faction allied with the player. Relations are symmetric.
+pub struct TeamRelations {
+    overrides: Vec<(Team, Team, Relation)>,
+}
+
+#[allow(dead_code)]
+impl TeamRelations {
+    pub fn new() -> Self {
+        TeamRelations { overrides: vec![] }
+    }
+
+    pub fn set(&mut self, first: Team, second: Team, relation: Relation) {
+        self.overrides
+            .retain(|(a, b, _)| !((*a == first && *b == second) || (*a == second && *b == first)));
+        self.overrides.push((first, second, relation));
+    }
+
+    pub fn relation(&self, first: &Team, second: &Team) -> Relation {
+        self.overrides
+            .iter()
+            .find(|(a, b, _)| (a == first && b == second) || (a == second && b == first))
+            .map(|(_, _, relation)| *relation)
+            .unwrap_or_else(|| first.default_relation(second))
+    }
+}
+
+// Characters without a team are treated as neutral
+pub fn find_team(world: &World, entity: Entity) -> Team {
+    world.get::<Team>(entity).cloned().unwrap_or(Team::Neutral)
+}
+
+pub fn relation_between(world: &World, first: Entity, second: Entity) -> Relation {
+    if first == second {
+        return Relation::Allied;
+    }
+    let first = find_team(world, first);
+    let second = find_team(world, second);
+    match world.get_resource::<TeamRelations>() {
+        Some(relations) => relations.relation(&first, &second),
+        None => first.default_relation(&second),
+    }
+}
+
+pub fn is_hostile(world: &World, first: Entity, second: Entity) -> bool {
+    relation_between(world, first, second) == Relation::Hostile
+}
+
+pub fn is_allied(world: &World, first: Entity, second: Entity) -> bool {
+    relation_between(world, first, second) == Relation::Allied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_relations() {
+        assert_eq!(Relation::Allied, Team::Player.default_relation(&Team::Player));
+        assert_eq!(Relation::Hostile, Team::Player.default_relation(&Team::Enemy));
+        assert_eq!(Relation::Indifferent, Team::Neutral.default_relation(&Team::Enemy));
+        assert_eq!(Relation::Allied, Team::Neutral.default_relation(&Team::Neutral));
+        assert_eq!(Relation::Hostile, Team::Faction("Bandits".to_string()).default_relation(&Team::Enemy));
+        assert_eq!(
+            Relation::Allied,
+            Team::Faction("Bandits".to_string()).default_relation(&Team::Faction("Bandits".to_string()))
+        );
+    }
+
+    #[test]
+    fn overrides_are_symmetric() {
+        let mut relations = TeamRelations::new();
+        let caravan = Team::Faction("Caravan".to_string());
+        relations.set(Team::Player, caravan.clone(), Relation::Allied);
+        assert_eq!(Relation::Allied, relations.relation(&caravan, &Team::Player));
+        relations.set(caravan.clone(), Team::Player, Relation::Hostile);
+        assert_eq!(Relation::Hostile, relations.relation(&Team::Player, &caravan));
+        assert_eq!(Relation::Hostile, relations.relation(&caravan, &Team::Enemy));
+    }
+
+    #[test]
+    fn entity_relations() {
+        let mut world = World::new();
+        let player = world.spawn().insert(Team::Player).id();
+        let ally = world.spawn().insert(Team::Player).id();
+        let enemy = world.spawn().insert(Team::Enemy).id();
+        let bystander = world.spawn().id();
+
+        assert!(is_allied(&world, player, player));
+        assert!(is_allied(&world, player, ally));
+        assert!(is_hostile(&world, ally, enemy));
+        assert!(!is_hostile(&world, player, bystander));
+        assert!(!is_allied(&world, player, bystander));
+
+        // A charmed enemy simply changes sides
+        world.entity_mut(enemy).insert(Team::Player);
+        assert!(is_allied(&world, player, enemy));
+    }
+}
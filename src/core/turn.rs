@@ -0,0 +1,18 @@
+use bevy_ecs::prelude::*;
+
+use super::{apply_fields, remove_dead, tick_fields, tick_lifetimes, tick_status_effects, update_fields, FieldRuleLog};
+
+// Called once the player has taken an action that uses up their turn
+pub fn end_turn(world: &mut World) {
+    apply_fields(world);
+
+    let fired = update_fields(world);
+    if let Some(mut log) = world.get_resource_mut::<FieldRuleLog>() {
+        log.fired = fired;
+    }
+
+    tick_status_effects(world);
+    tick_fields(world);
+    tick_lifetimes(world);
+    remove_dead(world);
+}
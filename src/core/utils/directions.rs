@@ -1,7 +1,9 @@
+use serde::{Deserialize, Serialize};
+
 use super::SizedPoint;
 
 #[allow(dead_code)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Hash, Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum Direction {
     None,
     North,
@@ -61,6 +63,21 @@ impl Direction {
         }
     }
 
+    pub fn is_diagonal(&self) -> bool {
+        matches!(self, Direction::NorthEast | Direction::SouthEast | Direction::SouthWest | Direction::NorthWest)
+    }
+
+    // The vertical and horizontal steps that make up a diagonal
+    pub fn split_diagonal(&self) -> Option<(Direction, Direction)> {
+        match self {
+            Direction::NorthEast => Some((Direction::North, Direction::East)),
+            Direction::SouthEast => Some((Direction::South, Direction::East)),
+            Direction::SouthWest => Some((Direction::South, Direction::West)),
+            Direction::NorthWest => Some((Direction::North, Direction::West)),
+            _ => None,
+        }
+    }
+
     pub fn opposite(&self) -> Direction {
         match self {
             Direction::North => Direction::South,
@@ -89,4 +106,12 @@ mod tests {
     fn opposite() {
         assert_eq!(Direction::North, Direction::South.opposite());
     }
+
+    #[test]
+    fn split_diagonal() {
+        assert!(Direction::SouthWest.is_diagonal());
+        assert!(!Direction::West.is_diagonal());
+        assert_eq!(Some((Direction::South, Direction::West)), Direction::SouthWest.split_diagonal());
+        assert_eq!(None, Direction::West.split_diagonal());
+    }
 }
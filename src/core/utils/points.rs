@@ -231,7 +231,6 @@ impl From<Point> for SizedPoint {
     }
 }
 
-#[allow(dead_code)]
 pub fn extend_line_along_path(points: &[Point], length: u32) -> Vec<Point> {
     let starting = points.first().unwrap();
     let ending = points.last().unwrap();
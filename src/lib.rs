@@ -3,7 +3,7 @@
 
 use std::{env, path};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use ggez::{conf, event, ContextBuilder};
 use winit::dpi::LogicalSize;
 
@@ -12,10 +12,24 @@ use ui::{GameState, GAME_HEIGHT, GAME_WIDTH};
 
 mod core;
 
+mod validate;
+
 pub fn run() -> Result<()> {
+    // `validate [data folder]` checks the data without starting the game
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(|a| a.as_str()) == Some("validate") {
+        return validate::run(args.get(2).map(path::PathBuf::from));
+    }
+
+    // `--map beach` or `--map beach:2` picks the battle's map, otherwise any map is fair game
+    let map = match args.iter().position(|a| a == "--map") {
+        Some(index) => core::MapRequest::parse(args.get(index + 1).ok_or_else(|| anyhow!("--map needs a map, such as beach or beach:2"))?)?,
+        None => core::MapRequest::Any,
+    };
+
     let (mut ctx, event_loop) = get_game_context().build()?;
 
-    let my_game = GameState::new(&mut ctx)?;
+    let my_game = GameState::new(&mut ctx, map)?;
     event::run(ctx, event_loop, my_game);
 }
 
@@ -31,10 +45,15 @@ fn get_game_context() -> ContextBuilder {
         });
 
     // Add ArenaGS-Data to the resource path
-    if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
-        let root = path::PathBuf::from(manifest_dir);
-        cb = cb.add_resource_path(root.join("..").join("ArenaGS-Data"));
+    if let Some(data) = data_directory() {
+        cb = cb.add_resource_path(data);
     }
 
     cb
 }
+
+fn data_directory() -> Option<path::PathBuf> {
+    env::var("CARGO_MANIFEST_DIR")
+        .ok()
+        .map(|manifest_dir| path::PathBuf::from(manifest_dir).join("..").join("ArenaGS-Data"))
+}
@@ -5,9 +5,12 @@ use ggez::mint::Vector2;
 use keyframe::{functions::Linear, AnimationSequence};
 use keyframe_derive::CanTween;
 
-use crate::core::{AnimationState, Appearance};
+use crate::core::{run_post_movement_actions, AnimationState, Appearance, Character, InputGate, PostMovementActionKind};
 
-use super::{MovementAnimationComplete, MovementAnimationEvent, SpriteAnimateActionCompleteEvent, SpriteAnimateActionEvent};
+use super::{
+    advance_effects, advance_floating_text, MovementAnimationComplete, MovementAnimationEvent, SpriteAnimateActionCompleteEvent, SpriteAnimateActionEvent,
+    SpriteSheets,
+};
 
 #[derive(CanTween, Debug, Clone)]
 pub struct MovementAnimation {
@@ -34,18 +37,24 @@ pub struct Animation {
     pub movement: Option<AnimationSequence<MovementAnimation>>,
 }
 
-pub enum PostMovementActionKind {
-    Despawn,
-}
-
 #[derive(Component)]
 pub struct PostMovementAction {
-    kind: PostMovementActionKind,
+    kinds: Vec<PostMovementActionKind>,
 }
 
 impl PostMovementAction {
+    #[allow(dead_code)]
     pub fn new(kind: PostMovementActionKind) -> Self {
-        PostMovementAction { kind }
+        PostMovementAction { kinds: vec![kind] }
+    }
+
+    // Run in order once the movement lands
+    pub fn sequence(kinds: Vec<PostMovementActionKind>) -> Self {
+        PostMovementAction { kinds }
+    }
+
+    pub fn kinds(&self) -> &[PostMovementActionKind] {
+        &self.kinds
     }
 }
 
@@ -55,11 +64,13 @@ impl Animation {
     }
 }
 
-pub fn create_movement_animation(start: Vector2<f32>, end: Vector2<f32>, duration: f32) -> AnimationSequence<MovementAnimation> {
-    AnimationSequence::from(vec![(start.into(), 0.0, Linear).into(), (end.into(), duration, Linear).into()])
-}
-
 fn find_idle_animation(world: &mut World, entity: Entity) -> AnimationSequence<f32> {
+    // Actions such as a cast or a hit play from their first frame
+    let appearance = world.get::<Appearance>(entity).unwrap();
+    if appearance.state != AnimationState::Idle {
+        return world.get_resource::<SpriteSheets>().unwrap().create_standard_sprite_animation(&appearance.kind);
+    }
+
     // Unlike other animations, the idle "bob" needs to be sync across all units for it
     // to look good. So if we have any animation end requests, find the first idle (if any)
     // and use it
@@ -78,7 +89,8 @@ fn find_idle_animation(world: &mut World, entity: Entity) -> AnimationSequence<f
     if let Some(existing_idle_animation) = existing_idle_animation {
         existing_idle_animation
     } else {
-        world.get::<Appearance>(entity).unwrap().create_standard_sprite_animation()
+        let kind = &world.get::<Appearance>(entity).unwrap().kind;
+        world.get_resource::<SpriteSheets>().unwrap().create_standard_sprite_animation(kind)
     }
 }
 
@@ -87,6 +99,8 @@ pub fn advance_all_animations(world: &mut World) {
 
     advance_sprite_animations(world);
     advance_movement_animations(world);
+    advance_effects(world);
+    advance_floating_text(world);
 }
 
 pub fn create_needed_idle_animations(world: &mut World) {
@@ -107,7 +121,7 @@ fn advance_sprite_animations(world: &mut World) {
     let mut sprite_completed = vec![];
     let mut query = world.query::<(Entity, &Appearance, &mut Animation)>();
     for (entity, appearance, mut animation) in query.iter_mut(world) {
-        let should_loop = matches!(appearance.state, AnimationState::Idle);
+        let should_loop = matches!(appearance.state, AnimationState::Idle | AnimationState::Walk);
 
         if let Some(sprite_animation) = &mut animation.sprite {
             if should_loop {
@@ -152,7 +166,11 @@ pub fn start_sprite_animations(mut requests: EventReader<SpriteAnimateActionEven
 }
 
 #[no_mangle]
-pub fn end_sprite_animation(mut requests: EventReader<SpriteAnimateActionCompleteEvent>, mut query: Query<(&mut Appearance, &mut Animation)>) {
+pub fn end_sprite_animation(
+    mut requests: EventReader<SpriteAnimateActionCompleteEvent>,
+    mut query: Query<(&mut Appearance, &mut Animation)>,
+    sheets: Res<SpriteSheets>,
+) {
     // Because we can note animations as complete in render thread, we can often get multiple
     // notifications of the same Entity being complete. This is fine as long as we de-duplicate them
     let requests: HashSet<SpriteAnimateActionCompleteEvent> = HashSet::from_iter(requests.iter().cloned());
@@ -182,7 +200,7 @@ pub fn end_sprite_animation(mut requests: EventReader<SpriteAnimateActionComplet
             if existing_idle_animation.is_some() {
                 animation.sprite = existing_idle_animation.clone();
             } else {
-                animation.sprite = Some(appearance.create_idle_animation());
+                animation.sprite = Some(sheets.create_idle_animation(&appearance.kind));
             }
         }
     }
@@ -195,37 +213,89 @@ fn distance(left: Vector2<f32>, right: Vector2<f32>) -> f32 {
     f32::sqrt((left.x - right.x).powi(2) + (left.y - right.y).powi(2))
 }
 
+fn movement_duration(start: Vector2<f32>, end: Vector2<f32>) -> f32 {
+    let distance = distance(start, end);
+    match distance {
+        x if (0.9..1.1).contains(&x) => MOVEMENT_ANIMATION_DURATION,
+        _ => LARGE_MOVEMENT_ANIMATION_DURATION * distance as f32,
+    }
+}
+
+pub fn create_path_animation(points: &[Vector2<f32>]) -> AnimationSequence<MovementAnimation> {
+    let mut time = 0.0;
+    let mut frames = vec![(points[0].into(), 0.0, Linear).into()];
+    for step in points.windows(2) {
+        time += movement_duration(step[0], step[1]);
+        frames.push((step[1].into(), time, Linear).into());
+    }
+    AnimationSequence::from(frames)
+}
+
 #[no_mangle]
-pub fn start_movement_animations(mut requests: EventReader<MovementAnimationEvent>, mut query: Query<&mut Animation>) {
+pub fn start_movement_animations(
+    mut requests: EventReader<MovementAnimationEvent>,
+    mut query: Query<(&mut Animation, Option<&mut Appearance>, Option<&Character>)>,
+    sheets: Res<SpriteSheets>,
+) {
+    // Requests that pick up where the last one for that entity ended are chained into a single path
+    let mut paths: Vec<(Entity, Vec<Vector2<f32>>)> = vec![];
     for request in requests.iter() {
-        let mut animation = query.get_mut(request.entity).expect("Starting movement animation on item without animation");
+        match paths
+            .iter_mut()
+            .find(|(entity, points)| *entity == request.entity && *points.last().unwrap() == request.start)
+        {
+            Some((_, points)) => points.push(request.end),
+            None => paths.push((request.entity, vec![request.start, request.end])),
+        }
+    }
+
+    for (entity, points) in paths {
+        // Anything killed by the movement that sent it, such as being shoved into a wall, is already gone
+        if let Ok((mut animation, appearance, character)) = query.get_mut(entity) {
+            animation.movement = Some(create_path_animation(&points));
 
-        let distance = distance(request.start, request.end);
-        let duration = match distance {
-            x if (0.9..1.1).contains(&x) => MOVEMENT_ANIMATION_DURATION,
-            _ => LARGE_MOVEMENT_ANIMATION_DURATION * distance as f32,
-        };
-        animation.movement = Some(create_movement_animation(request.start, request.end, duration));
+            // Characters walk for as long as the movement lasts, unless already busy with something else
+            if let (Some(mut appearance), Some(_)) = (appearance, character) {
+                if appearance.state == AnimationState::Idle {
+                    appearance.state = AnimationState::Walk;
+                    animation.sprite = Some(sheets.create_walk_animation(&appearance.kind));
+                }
+            }
+        }
     }
 }
 
 #[no_mangle]
 pub fn end_movement_animation(
     mut requests: EventReader<MovementAnimationComplete>,
-    mut query: Query<(&mut Animation, Option<&mut PostMovementAction>)>,
+    mut query: Query<(&mut Animation, Option<&Appearance>, Option<&PostMovementAction>)>,
+    mut sprite_complete: EventWriter<SpriteAnimateActionCompleteEvent>,
     mut commands: Commands,
 ) {
     for request in requests.iter() {
         let entity = request.entity;
-        if let Ok((mut animation, action)) = query.get_mut(entity) {
+        if let Ok((mut animation, appearance, action)) = query.get_mut(entity) {
             animation.movement = None;
+            // Ending the walk goes through the usual path back to a synced idle
+            if appearance.map(|a| a.state == AnimationState::Walk).unwrap_or(false) {
+                sprite_complete.send(SpriteAnimateActionCompleteEvent::new(entity));
+            }
             if let Some(action) = action {
-                match action.kind {
-                    PostMovementActionKind::Despawn => commands.add(move |w: &mut World| {
-                        w.despawn(entity);
-                    }),
-                }
+                let kinds = action.kinds().to_vec();
+                commands.add(move |w: &mut World| {
+                    run_post_movement_actions(w, entity, &kinds);
+                });
             }
         }
     }
 }
+
+// Player input waits on anything still moving and on characters acting out a skill
+#[no_mangle]
+pub fn update_input_gate(mut gate: ResMut<InputGate>, query: Query<(&Animation, Option<&Appearance>, Option<&Character>)>) {
+    let busy = query.iter().any(|(animation, appearance, character)| {
+        let acting = character.is_some() && appearance.map(|a| a.state != AnimationState::Idle).unwrap_or(false);
+        animation.movement.is_some() || acting
+    });
+    gate.set_busy(busy);
+}
@@ -0,0 +1,113 @@
+use anyhow::Result;
+use bevy_ecs::prelude::*;
+use ggez::Context;
+
+use crate::core::{Map, MapIndex, Skills, StatusEffectKind};
+
+use super::{read_preload_list, skill_icon, BackingImage, ImageCache, SpriteSheets};
+
+// Drawn directly by name from the battle ui
+const UI_IMAGES: [&str; 6] = [
+    "/ui/skillbar_frame.png",
+    "/ui/life_frame.png",
+    "/ui/life_bar.png",
+    "/ui/absorb_bar.png",
+    "/images/frames/small_frame.png",
+    "/images/frames/large_frame.png",
+];
+
+const STATUS_KINDS: [StatusEffectKind; 5] = [
+    StatusEffectKind::Burning,
+    StatusEffectKind::Stunned,
+    StatusEffectKind::Hasted,
+    StatusEffectKind::Shielded,
+    StatusEffectKind::Slowed,
+];
+
+pub struct ImageReference {
+    pub description: String,
+    // None when nothing has been assigned at all, such as a skill with no icon
+    pub image: Option<String>,
+}
+
+impl ImageReference {
+    fn new(description: String, image: &str) -> Self {
+        ImageReference {
+            description,
+            image: Some(image.to_string()),
+        }
+    }
+
+    fn unassigned(description: String) -> Self {
+        ImageReference { description, image: None }
+    }
+
+    pub fn describe(&self) -> String {
+        match &self.image {
+            Some(image) => format!("{} ({})", self.description, image),
+            None => format!("{} (no image assigned)", self.description),
+        }
+    }
+}
+
+// Every image the ui or the data in the world can ask to draw
+pub fn referenced_images(world: &mut World) -> Vec<ImageReference> {
+    let mut skill_names: Vec<String> = world
+        .query::<&Skills>()
+        .iter(world)
+        .flat_map(|s| s.skills.iter().map(|skill| skill.name.clone()))
+        .collect();
+    skill_names.sort();
+    skill_names.dedup();
+
+    let mut references = vec![];
+    for image in UI_IMAGES {
+        references.push(ImageReference::new("Interface".to_string(), image));
+    }
+    for (kind, variant) in world.get_resource::<MapIndex>().unwrap().maps() {
+        references.push(ImageReference::new(format!("Map {:?} {}", kind, variant), &kind.image_path(*variant)));
+    }
+    for kind in STATUS_KINDS {
+        references.push(ImageReference::new(format!("Status icon {}", kind.name()), kind.filename()));
+    }
+    for sheet in world.get_resource::<SpriteSheets>().unwrap().iter() {
+        references.push(ImageReference::new(format!("Sprite sheet {}", sheet.appearance.name()), &sheet.image));
+    }
+    for name in skill_names {
+        match skill_icon(&name) {
+            Some(icon) => references.push(ImageReference::new(format!("Skill icon {}", name), icon)),
+            None => references.push(ImageReference::unassigned(format!("Skill icon {}", name))),
+        }
+    }
+    references
+}
+
+// Everything that would be drawn as a placeholder, so a typo is noticed at startup
+pub fn find_missing_assets(world: &mut World) -> Vec<String> {
+    let references = referenced_images(world);
+    let images = world.get_resource::<ImageCache>().unwrap();
+    references
+        .iter()
+        .filter(|r| r.image.as_ref().map(|i| !images.contains(i)).unwrap_or(true))
+        .map(|r| r.describe())
+        .collect()
+}
+
+pub fn report_missing_assets(world: &mut World) {
+    let missing = find_missing_assets(world);
+    if !missing.is_empty() {
+        eprintln!("Warning: {} missing asset(s) will be drawn as placeholders:", missing.len());
+        for asset in missing {
+            eprintln!("    {}", asset);
+        }
+    }
+}
+
+// Only the current map and anything listed in the preload file are decoded up front,
+// with everything else left until it is first drawn so startup does not grow with the data
+pub fn preload_images(world: &mut World, ctx: &mut Context) -> Result<()> {
+    let mut names = vec![world.get_resource::<Map>().unwrap().image_path()];
+    names.extend(read_preload_list(&ctx.fs)?);
+    world.get_resource_mut::<ImageCache>().unwrap().preload(ctx, &names);
+    Ok(())
+}
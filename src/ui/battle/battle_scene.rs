@@ -1,4 +1,5 @@
 use bevy_ecs::prelude::*;
+use ggez::event::MouseButton;
 use ggez::graphics::{self, Color, Rect};
 use ggez::{graphics::Canvas, input::keyboard::KeyInput};
 use winit::event::VirtualKeyCode;
@@ -10,20 +11,50 @@ use crate::core::*;
 use crate::ui::*;
 
 #[no_mangle]
-pub fn battle_update(_world: &mut World, _ctx: &mut ggez::Context) {}
+pub fn battle_update(world: &mut World, _ctx: &mut ggez::Context) {
+    let ready = world.get_resource_mut::<InputGate>().unwrap().take_ready();
+    if let Some(command) = ready {
+        run_command(world, command);
+    }
+}
+
+// Player commands go through the input gate so they do not pile up on top of running animations
+pub fn submit_command(world: &mut World, command: PlayerCommand) {
+    let ready = world.get_resource_mut::<InputGate>().unwrap().submit(command);
+    if let Some(command) = ready {
+        run_command(world, command);
+    }
+}
+
+fn run_command(world: &mut World, command: PlayerCommand) {
+    // Hold the gate until the animations this starts have been picked up
+    world.get_resource_mut::<InputGate>().unwrap().set_busy(true);
+
+    match command {
+        PlayerCommand::Move(direction) => move_to(world, direction),
+        PlayerCommand::WalkTo(target) => walk_to(world, target),
+        PlayerCommand::UseSkill(skill, target) => {
+            // A queued skill may no longer have a valid target
+            let player = find_player(world);
+            if is_valid_target(world, player, &skill, target) {
+                invoke_skill(world, player, &skill, target);
+            }
+        }
+    }
+}
 
 #[no_mangle]
 pub fn battle_draw(world: &mut World, ctx: &mut ggez::Context, canvas: &mut Canvas) {
-    world.get_resource_mut::<Frame>().unwrap().current += 1;
-    animation::advance_all_animations(world);
-
     draw_map(world, canvas);
+    draw_movement_preview(world, ctx, canvas);
     draw_status(world, canvas);
     message_draw(world, ctx, canvas);
     skillbar_draw(world, canvas);
 
     draw_sprites(world, canvas);
     draw_fields(world, ctx, canvas);
+    draw_effects(world, canvas);
+    draw_floating_text(world, canvas);
 }
 
 const FIELD_SIZE: Rect = Rect::new(TILE_BORDER, TILE_BORDER, TILE_SIZE - TILE_BORDER, TILE_SIZE - TILE_BORDER);
@@ -32,6 +63,12 @@ fn draw_fields(world: &mut World, ctx: &mut ggez::Context, canvas: &mut Canvas)
     for field in &world.query::<&Fields>().iter(world).collect::<Vec<_>>() {
         let color = match field.color {
             FieldColor::Gray => Color::new(122.0 / 255.0, 72.0 / 255.0, 60.0 / 255.0, 140.0 / 255.0),
+            FieldColor::Red => Color::new(220.0 / 255.0, 80.0 / 255.0, 20.0 / 255.0, 140.0 / 255.0),
+            FieldColor::Green => Color::new(80.0 / 255.0, 160.0 / 255.0, 40.0 / 255.0, 140.0 / 255.0),
+            FieldColor::Blue => Color::new(40.0 / 255.0, 90.0 / 255.0, 200.0 / 255.0, 140.0 / 255.0),
+            FieldColor::Black => Color::new(20.0 / 255.0, 20.0 / 255.0, 20.0 / 255.0, 200.0 / 255.0),
+            FieldColor::White => Color::new(220.0 / 255.0, 240.0 / 255.0, 250.0 / 255.0, 140.0 / 255.0),
+            FieldColor::Yellow => Color::new(240.0 / 255.0, 220.0 / 255.0, 40.0 / 255.0, 140.0 / 255.0),
         };
         let square = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), FIELD_SIZE, color).unwrap();
         for position in &field.positions {
@@ -42,11 +79,32 @@ fn draw_fields(world: &mut World, ctx: &mut ggez::Context, canvas: &mut Canvas)
 }
 
 fn draw_sprites(world: &mut World, canvas: &mut Canvas) {
-    for (appearance, animation, position) in &world.query::<(&Appearance, &Animation, &Position)>().iter(world).collect::<Vec<_>>() {
+    for (entity, appearance, animation, position, facing, health, effects) in &world
+        .query::<(
+            Entity,
+            &Appearance,
+            &Animation,
+            &Position,
+            Option<&Facing>,
+            Option<&Health>,
+            Option<&StatusEffects>,
+        )>()
+        .iter(world)
+        .collect::<Vec<_>>()
+    {
         let screen_position = calculate_screen_position(animation, position);
         let images = world.get_resource::<ImageCache>().unwrap();
-        draw::render_sprite(canvas, screen_position, appearance, animation, images);
-        overlay::render_sprite(canvas, screen_position, position, false, images);
+        let sheets = world.get_resource::<SpriteSheets>().unwrap();
+        let facing = facing.map(|f| f.direction).unwrap_or(Direction::None);
+        draw::render_sprite(canvas, screen_position, appearance, animation, facing, images, sheets);
+        if let Some(health) = health {
+            let stats = OverlayStats {
+                life_percentage: health.life_percentage(),
+                absorb_percentage: absorb_percentage(world, *entity),
+                effects: *effects,
+            };
+            overlay::render_sprite(canvas, screen_position, position, &stats, false, images);
+        }
     }
 }
 
@@ -61,6 +119,68 @@ fn calculate_screen_position(animation: &Animation, position: &Position) -> Vec2
     }
 }
 
+// The map tile under the mouse, if any
+pub struct HoveredTile {
+    pub position: Option<Point>,
+}
+
+fn draw_movement_preview(world: &mut World, ctx: &mut ggez::Context, canvas: &mut Canvas) {
+    let hovered = match world.get_resource::<HoveredTile>().and_then(|h| h.position) {
+        Some(hovered) => hovered,
+        None => return,
+    };
+    let player = find_player(world);
+    if let Some(path) = find_path(world, player, hovered) {
+        // Steps past what can be walked this turn are shown dimmed
+        let reachable = movement_points(world, player) as usize;
+        let reachable_square = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), FIELD_SIZE, Color::new(1.0, 1.0, 1.0, 0.4)).unwrap();
+        let unreachable_square = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), FIELD_SIZE, Color::new(0.4, 0.4, 0.4, 0.4)).unwrap();
+        for (i, step) in path.iter().enumerate() {
+            let square = if i < reachable { &reachable_square } else { &unreachable_square };
+            for p in step.covered_points() {
+                canvas.draw(square, screen_point_for_map_grid(p.x as f32, p.y as f32));
+            }
+        }
+    }
+}
+
+#[no_mangle]
+pub fn battle_mouse_motion_event(world: &mut World, ctx: &mut ggez::Context, x: f32, y: f32, _dx: f32, _dy: f32) {
+    let (x, y) = world.get_resource::<ScreenCoordinates>().unwrap().logical_mouse_position(ctx, x, y);
+    world.insert_resource(HoveredTile {
+        position: screen_to_map_position(x, y),
+    });
+}
+
+#[no_mangle]
+pub fn battle_mouse_button_up_event(world: &mut World, ctx: &mut ggez::Context, button: ggez::event::MouseButton, x: f32, y: f32) {
+    let (x, y) = world.get_resource::<ScreenCoordinates>().unwrap().logical_mouse_position(ctx, x, y);
+
+    if button == MouseButton::Left {
+        if let Some(target) = screen_to_map_position(x, y) {
+            submit_command(world, PlayerCommand::WalkTo(target));
+        }
+    }
+}
+
+fn walk_to(world: &mut World, target: Point) {
+    let player = find_player(world);
+    let path = match find_path(world, player, target) {
+        Some(path) => path,
+        None => return,
+    };
+    if !can_act(world, player) {
+        world.send_event(NewMessageEvent::new("Stunned!"));
+        end_turn(world);
+        return;
+    }
+
+    let steps = std::cmp::min(movement_points(world, player) as usize, path.len());
+    if move_along_path(world, player, &path[..steps]) > 0 {
+        end_turn(world);
+    }
+}
+
 #[no_mangle]
 pub fn battle_key_up_event(world: &mut World, _ctx: &mut ggez::Context, input: KeyInput) {
     match input.keycode {
@@ -68,76 +188,57 @@ pub fn battle_key_up_event(world: &mut World, _ctx: &mut ggez::Context, input: K
             world.get_resource_mut::<Scenes>().unwrap().push(SceneKind::DebugOverlay);
             world.insert_resource(DebugOverlayRequest::new(DebugKind::MapOverlay));
         }
+        Some(VirtualKeyCode::F2) => {
+            world.get_resource_mut::<Scenes>().unwrap().push(SceneKind::DebugOverlay);
+            world.insert_resource(DebugOverlayRequest::new(DebugKind::FieldRules));
+        }
+        Some(VirtualKeyCode::F3) => {
+            let fast_mode = {
+                let mut gate = world.get_resource_mut::<InputGate>().unwrap();
+                gate.fast_mode = !gate.fast_mode;
+                gate.fast_mode
+            };
+            let state = if fast_mode { "on" } else { "off" };
+            world.send_event(NewMessageEvent::new(&format!("Fast mode {}.", state)));
+        }
         Some(VirtualKeyCode::D) => {
             let player = find_player(world);
             world.send_event(SpriteAnimateActionEvent::new(player, AnimationState::Cheer));
             let frame = world.get_resource::<Frame>().unwrap().current;
             world.send_event(NewMessageEvent::new(&format!("Dance Party: {}", frame)));
         }
-        Some(VirtualKeyCode::Left) => {
-            move_to(world, Direction::West);
-        }
-        Some(VirtualKeyCode::Right) => {
-            move_to(world, Direction::East);
-        }
-        Some(VirtualKeyCode::Up) => {
-            move_to(world, Direction::North);
-        }
-        Some(VirtualKeyCode::Down) => {
-            move_to(world, Direction::South);
-        }
-        Some(VirtualKeyCode::F) => {
-            let player = find_player(world);
-            let position = find_position(world, player).unwrap();
-
-            let target = SizedPoint::new_sized(3, 3, 2, 2);
-            let bolt = world
-                .spawn()
-                .insert(Position::from(target))
-                .insert(Appearance::new(AppearanceKind::FireBolt))
-                .insert(Animation::new())
-                .insert(PostMovementAction::new(PostMovementActionKind::Despawn))
-                .id();
-            world.send_event(MovementAnimationEvent::new(bolt, position.visual_center(), target.visual_center()))
-        }
         Some(VirtualKeyCode::PageUp) => world.send_event(ScrollMessageEvent::page_up()),
         Some(VirtualKeyCode::PageDown) => world.send_event(ScrollMessageEvent::page_down()),
         Some(VirtualKeyCode::End) => world.send_event(ScrollMessageEvent::scroll_to_end()),
-        Some(VirtualKeyCode::Key1) => {
-            let player = find_player(world);
-            let skill = world.get::<Skills>(player).unwrap().skills[0].clone();
-            world.insert_resource(TargetRequest::new(skill));
-            world.get_resource_mut::<Scenes>().unwrap().push(SceneKind::Target);
-        }
-        Some(VirtualKeyCode::Key2) => {
-            let player = find_player(world);
-            let skill = world.get::<Skills>(player).unwrap().skills[1].clone();
-            world.insert_resource(TargetRequest::new(skill));
-            world.get_resource_mut::<Scenes>().unwrap().push(SceneKind::Target);
+        Some(keycode) => {
+            let direction = world.get_resource::<KeyBindings>().unwrap().movement_direction(keycode);
+            if let Some(direction) = direction {
+                submit_command(world, PlayerCommand::Move(direction));
+            } else if let Some(index) = map_hotkey_to_index(keycode) {
+                start_targeting(world, index);
+            }
         }
-        _ => {}
+        None => {}
+    }
+}
+
+fn start_targeting(world: &mut World, index: usize) {
+    let player = find_player(world);
+    if let Some(skill) = world.get::<Skills>(player).unwrap().skills.get(index).cloned() {
+        world.insert_resource(TargetRequest::new(skill));
+        world.get_resource_mut::<Scenes>().unwrap().push(SceneKind::Target);
     }
 }
 
 fn move_to(world: &mut World, direction: Direction) {
-    let event = {
-        let player = find_player(world);
-        let mut position = world.get_mut::<Position>(player).unwrap();
-        let current_position = position.position;
-        if let Some(new_position) = current_position.in_direction(direction) {
-            position.position = new_position;
-            Some(MovementAnimationEvent::new(
-                player,
-                current_position.visual_center(),
-                new_position.visual_center(),
-            ))
-        } else {
-            None
-        }
-    };
-    if let Some(event) = event {
-        world.send_event(event);
+    let player = find_player(world);
+    if !can_act(world, player) {
+        world.send_event(NewMessageEvent::new("Stunned!"));
+        end_turn(world);
+        return;
     }
+
+    try_move(world, player, direction);
 }
 
 #[no_mangle]
@@ -147,18 +248,6 @@ pub fn battle_draw_previous() -> bool {
 
 fn draw_map(world: &mut World, canvas: &mut Canvas) {
     let map = world.get_resource::<Map>().unwrap();
-    let map_image = map.kind.filename().to_string();
+    let map_image = map.image_path();
     draw_image(canvas, world, &map_image, MAP_IMAGE_POSITION);
 }
-
-impl BackingImage for MapKind {
-    fn filename(&self) -> &str {
-        match self {
-            MapKind::Ashlands => "/maps/ashlands/map1.png",
-            MapKind::Beach => "/maps/beach/map1.png",
-            MapKind::Desert => "/maps/desert/map1.png",
-            MapKind::Ruins => "/maps/ruins/map1.png",
-            MapKind::Winter => "/maps/winter/map1.png",
-        }
-    }
-}
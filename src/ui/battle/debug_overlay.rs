@@ -8,7 +8,7 @@ use ggez::{
 use winit::event::VirtualKeyCode;
 
 use crate::{
-    core::{Map, Point},
+    core::{FieldRuleLog, Map, Point, FIELD_RULES},
     ui::*,
 };
 
@@ -19,6 +19,7 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub enum DebugKind {
     MapOverlay,
+    FieldRules,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -65,6 +66,26 @@ pub fn debug_draw(world: &mut World, ctx: &mut ggez::Context, canvas: &mut Canva
                 }
             }
         }
+        DebugKind::FieldRules => {
+            let yellow_square = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), SQUARE_SIZE, Color::new(0.9, 0.9, 0.1, 0.5)).unwrap();
+
+            draw_map_grid(canvas, ctx);
+
+            let log = world.get_resource::<FieldRuleLog>().unwrap();
+            for fired in &log.fired {
+                canvas.draw(&yellow_square, screen_point_for_map_grid(fired.position.x as f32, fired.position.y as f32));
+            }
+
+            let mut y = 40.0;
+            for (i, rule) in FIELD_RULES.iter().enumerate() {
+                let count = log.fired.iter().filter(|f| f.rule == i).count();
+                canvas.draw(
+                    graphics::Text::new(format!("{} ({})", rule.name, count)).set_font("default").set_scale(18.0),
+                    Vec2::new(875.0, y),
+                );
+                y += 22.0;
+            }
+        }
     }
 }
 
@@ -72,7 +93,8 @@ pub fn debug_draw(world: &mut World, ctx: &mut ggez::Context, canvas: &mut Canva
 pub fn debug_mouse_button_up_event(world: &mut World, ctx: &mut ggez::Context, button: ggez::event::MouseButton, x: f32, y: f32) {
     let (x, y) = world.get_resource::<ScreenCoordinates>().unwrap().logical_mouse_position(ctx, x, y);
 
-    if button == MouseButton::Left {
+    let overlay_kind = world.get_resource::<DebugOverlayRequest>().unwrap().kind;
+    if button == MouseButton::Left && matches!(overlay_kind, DebugKind::MapOverlay) {
         if let Some(point) = screen_to_map_position(x, y) {
             let mut map = world.get_resource_mut::<Map>().unwrap();
             let was_walkable = map.is_walkable(&point);
@@ -84,7 +106,7 @@ pub fn debug_mouse_button_up_event(world: &mut World, ctx: &mut ggez::Context, b
 #[no_mangle]
 pub fn debug_key_up_event(world: &mut World, _ctx: &mut ggez::Context, input: KeyInput) {
     match input.keycode {
-        Some(VirtualKeyCode::F1) => {
+        Some(VirtualKeyCode::F1) | Some(VirtualKeyCode::F2) => {
             world.get_resource_mut::<Scenes>().unwrap().pop();
             world.remove_resource::<DebugOverlayRequest>();
         }
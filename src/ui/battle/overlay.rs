@@ -4,21 +4,70 @@ use ggez::{
     mint::{self, Point2},
 };
 
-use crate::{core::Position, ui::ImageCache};
+use crate::{
+    core::{Position, StatusEffectKind, StatusEffects},
+    ui::{BackingImage, ImageCache},
+};
 
 use super::TILE_SIZE;
 
-pub fn render_sprite(canvas: &mut Canvas, screen_position: Vec2, position: &Position, draw_bracket: bool, images: &ImageCache) {
+pub struct OverlayStats<'a> {
+    pub life_percentage: f32,
+    pub absorb_percentage: f32,
+    pub effects: Option<&'a StatusEffects>,
+}
+
+pub fn render_sprite(canvas: &mut Canvas, screen_position: Vec2, position: &Position, stats: &OverlayStats, draw_bracket: bool, images: &ImageCache) {
     let size = overlay_size(position);
+    let top = screen_position.y - (TILE_SIZE * size as f32) / 2.0;
     let screen_position = Vec2 {
         x: screen_position.x + 3.0 - (TILE_SIZE * size as f32) / 2.0,
         y: screen_position.y - 2.0 + (TILE_SIZE * size as f32) / 2.0,
     };
 
-    render_lifebar(canvas, screen_position, 0.9, 0.0, size, images);
+    render_lifebar(canvas, screen_position, stats.life_percentage, stats.absorb_percentage, size, images);
     if draw_bracket {
         render_bracket(canvas, screen_position, size, images);
     }
+    if let Some(effects) = stats.effects {
+        render_status_icons(canvas, Vec2::new(screen_position.x, top), effects, images);
+    }
+}
+
+const STATUS_ICON_SIZE: f32 = 16.0;
+
+fn render_status_icons(canvas: &mut Canvas, position: Vec2, effects: &StatusEffects, images: &ImageCache) {
+    for (i, effect) in effects.effects.iter().enumerate() {
+        let image = images.get(effect.kind.filename());
+        let icon_params = DrawParam {
+            transform: Transform::Values {
+                dest: Point2 {
+                    x: position.x + (STATUS_ICON_SIZE + 1.0) * i as f32,
+                    y: position.y - STATUS_ICON_SIZE,
+                },
+                rotation: 0.0,
+                scale: mint::Vector2 {
+                    x: STATUS_ICON_SIZE / image.width() as f32,
+                    y: STATUS_ICON_SIZE / image.height() as f32,
+                },
+                offset: mint::Point2 { x: 0.0, y: 0.0 },
+            },
+            ..Default::default()
+        };
+        canvas.draw(image, icon_params);
+    }
+}
+
+impl BackingImage for StatusEffectKind {
+    fn filename(&self) -> &str {
+        match self {
+            StatusEffectKind::Burning => "/icons/spell/SpellBook01_15.png",
+            StatusEffectKind::Stunned => "/icons/spell/SpellBook06_100.png",
+            StatusEffectKind::Hasted => "/icons/spell/SpellBook03_76.png",
+            StatusEffectKind::Shielded => "/icons/spell/SpellBook01_84.png",
+            StatusEffectKind::Slowed => "/icons/spell/SpellBook06_22.png",
+        }
+    }
 }
 
 fn render_bracket(canvas: &mut Canvas, position: Vec2, scale: u32, images: &ImageCache) {
@@ -85,12 +134,7 @@ fn render_lifebar(canvas: &mut Canvas, position: Vec2, life_percentage: f32, abs
     canvas.draw(frame_image, frame_params);
 }
 
+// Odd footprints are framed as the smallest square that covers them
 fn overlay_size(position: &Position) -> u32 {
-    if position.position.width == 1 && position.position.height == 1 {
-        1
-    } else if position.position.width == 2 && position.position.height == 2 {
-        2
-    } else {
-        panic!();
-    }
+    position.position.width.max(position.position.height).max(1)
 }
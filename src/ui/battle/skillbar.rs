@@ -3,10 +3,11 @@ use ggez::{
     glam::Vec2,
     graphics::{self, Canvas, TextLayout},
 };
+use winit::event::VirtualKeyCode;
 
 use crate::{
     core::{find_player, Skill, Skills},
-    ui::{BackingImage, ImageCache, GAME_HEIGHT, GAME_WIDTH},
+    ui::{BackingImage, ImageCache, GAME_HEIGHT, GAME_WIDTH, MISSING_IMAGE},
 };
 
 const BORDER_WIDTH: f32 = 4.0;
@@ -59,16 +60,39 @@ pub fn map_index_to_hotkey(index: usize) -> usize {
     }
 }
 
+pub fn map_hotkey_to_index(key: VirtualKeyCode) -> Option<usize> {
+    match key {
+        VirtualKeyCode::Key1 => Some(0),
+        VirtualKeyCode::Key2 => Some(1),
+        VirtualKeyCode::Key3 => Some(2),
+        VirtualKeyCode::Key4 => Some(3),
+        VirtualKeyCode::Key5 => Some(4),
+        VirtualKeyCode::Key6 => Some(5),
+        VirtualKeyCode::Key7 => Some(6),
+        VirtualKeyCode::Key8 => Some(7),
+        VirtualKeyCode::Key9 => Some(8),
+        VirtualKeyCode::Key0 => Some(9),
+        _ => None,
+    }
+}
+
 fn get_skillbar_offset(skills: &[Skill]) -> f32 {
     (MAX_ICON_COUNT as f32 - skills.len() as f32) * (ICON_SIZE + BORDER_WIDTH) / 2.0
 }
 
+pub fn skill_icon(name: &str) -> Option<&'static str> {
+    match name {
+        "Shoot" => Some("/icons/items/gun_08_b.PNG"),
+        "Dodge" => Some("/icons/spell/SpellBook02_44.png"),
+        "Explosive Shot" => Some("/icons/items/gun_08_b.PNG"),
+        "Shove" => Some("/icons/spell/SpellBook02_44.png"),
+        "Summon Golem" => Some("/icons/spell/SpellBook02_44.png"),
+        _ => None,
+    }
+}
+
 impl BackingImage for Skill {
     fn filename(&self) -> &str {
-        match self.name.as_str() {
-            "Shoot" => "/icons/items/gun_08_b.PNG",
-            "Dodge" => "/icons/spell/SpellBook02_44.png",
-            _ => panic!(),
-        }
+        skill_icon(&self.name).unwrap_or(MISSING_IMAGE)
     }
 }
@@ -4,14 +4,22 @@ use ggez::{
     graphics::{self, Canvas},
 };
 
-use crate::core::{Player, Position};
+use crate::core::{Health, Player, Position, StatusEffects};
 
 pub fn draw_status(world: &mut World, canvas: &mut Canvas) {
-    let query = &mut world.query_filtered::<&Position, With<Player>>();
-    let position = query.single(world);
+    let query = &mut world.query_filtered::<(&Position, &Health, &StatusEffects), With<Player>>();
+    let (position, health, effects) = query.single(world);
 
     let mut offset = 30.0;
     draw_status_line(canvas, &format!("Position: {}", position.position.origin), 875.0, &mut offset);
+    draw_status_line(canvas, &format!("Health: {}/{}", health.current, health.max), 875.0, &mut offset);
+    for effect in &effects.effects {
+        let line = match effect.duration {
+            Some(duration) => format!("{} ({})", effect.kind.name(), duration),
+            None => effect.kind.name().to_string(),
+        };
+        draw_status_line(canvas, &line, 875.0, &mut offset);
+    }
     offset = 230.0;
     draw_status_line(canvas, "Enemies:", 875.0, &mut offset);
 
@@ -1,14 +1,15 @@
 use bevy_ecs::prelude::*;
 use ggez::{
     self,
+    event::MouseButton,
     graphics::{self, Canvas, Color, Rect},
     input::keyboard::KeyInput,
 };
 use winit::event::VirtualKeyCode;
 
-use super::{screen_point_for_map_grid, screen_to_map_position, TILE_SIZE};
+use super::{battle_scene::submit_command, screen_point_for_map_grid, screen_to_map_position, TILE_SIZE};
 use crate::{
-    core::{find_player, find_position, is_valid_target, Point, Skill},
+    core::{find_player, find_position, is_valid_target, skill_area, PlayerCommand, Point, Skill},
     ui::{Scenes, ScreenCoordinates, TILE_BORDER},
 };
 
@@ -53,6 +54,9 @@ pub fn targeting_draw(world: &mut World, ctx: &mut ggez::Context, canvas: &mut C
                     draw_line(&points, color, ctx, canvas);
                 }
             }
+
+            let area = skill_area(world, player, skill, cursor_point_on_map);
+            draw_area(&area, cursor_point_on_map, Color { a: 0.4, ..color }, ctx, canvas);
         }
     });
 }
@@ -66,8 +70,32 @@ fn draw_line(points: &[Point], color: Color, ctx: &mut ggez::Context, canvas: &m
     }
 }
 
+fn draw_area(points: &[Point], cursor: Point, color: Color, ctx: &mut ggez::Context, canvas: &mut Canvas) {
+    let square = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), TARGET_SIZE, color).unwrap();
+
+    // The cursor has already been drawn
+    for p in points.iter().filter(|p| **p != cursor) {
+        let screen_point = screen_point_for_map_grid(p.x as f32, p.y as f32);
+        canvas.draw(&square, screen_point);
+    }
+}
+
 #[no_mangle]
-pub fn targeting_mouse_button_up_event(_world: &mut World, _ctx: &mut ggez::Context, _button: ggez::event::MouseButton, _x: f32, _y: f32) {}
+pub fn targeting_mouse_button_up_event(world: &mut World, ctx: &mut ggez::Context, button: ggez::event::MouseButton, x: f32, y: f32) {
+    let (x, y) = world.get_resource::<ScreenCoordinates>().unwrap().logical_mouse_position(ctx, x, y);
+
+    if button == MouseButton::Left {
+        if let Some(target) = screen_to_map_position(x, y) {
+            let player = find_player(world);
+            let skill = world.get_resource::<TargetRequest>().unwrap().skill.clone();
+            if is_valid_target(world, player, &skill, target) {
+                world.remove_resource::<TargetRequest>();
+                world.get_resource_mut::<Scenes>().unwrap().pop();
+                submit_command(world, PlayerCommand::UseSkill(skill, target));
+            }
+        }
+    }
+}
 
 #[no_mangle]
 pub fn targeting_key_up_event(world: &mut World, _ctx: &mut ggez::Context, input: KeyInput) {
@@ -0,0 +1,55 @@
+use anyhow::{bail, Result};
+use bevy_ecs::prelude::*;
+use ggez::Context;
+
+use crate::core::{find_characters_blocked_by, Map};
+
+use super::{preload_images, FileWatcher, KeyBindings, SpriteSheets, KEY_BINDINGS_PATH, PRELOAD_PATH, SPRITES_PATH};
+
+// Skill and summon definitions are still built in code, so for now these and the current map are the data files there are
+const WATCHED_DATA: [&str; 3] = [SPRITES_PATH, KEY_BINDINGS_PATH, PRELOAD_PATH];
+
+// Re-applies data files to the running battle as they are saved, like code hot reload does for systems
+pub struct DataWatcher {
+    watcher: FileWatcher,
+}
+
+impl DataWatcher {
+    pub fn new(world: &World) -> Self {
+        let mut watcher = FileWatcher::new();
+        for path in WATCHED_DATA {
+            watcher.watch(path);
+        }
+        watcher.watch(&world.get_resource::<Map>().unwrap().data_path());
+        DataWatcher { watcher }
+    }
+
+    pub fn update(&mut self, world: &mut World, ctx: &mut Context) {
+        for path in self.watcher.changed() {
+            match reload_data(&path, world, ctx) {
+                Ok(()) => eprintln!("Reloaded {}", path),
+                // A half saved or broken file should not end the session
+                Err(e) => eprintln!("Warning: unable to reload {} ({}), keeping the previous version", path, e),
+            }
+        }
+    }
+}
+
+fn reload_data(path: &str, world: &mut World, ctx: &mut Context) -> Result<()> {
+    match path {
+        SPRITES_PATH => world.insert_resource(SpriteSheets::load(&ctx.fs)?),
+        KEY_BINDINGS_PATH => world.insert_resource(KeyBindings::load(&ctx.fs)?),
+        PRELOAD_PATH => preload_images(world, ctx)?,
+        _ => {
+            let variant = world.get_resource::<Map>().unwrap().variant;
+            let map = Map::load(ctx.fs.open(path)?, variant)?;
+            // Walls can not appear under anyone mid battle
+            let blocked = find_characters_blocked_by(world, &map).len();
+            if blocked > 0 {
+                bail!("{} character(s) would be standing on walls", blocked);
+            }
+            world.insert_resource(map);
+        }
+    }
+    Ok(())
+}
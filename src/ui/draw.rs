@@ -1,36 +1,78 @@
 use bevy_ecs::world::World;
 use ggez::{
     glam::Vec2,
-    graphics::{self, Canvas, Color, DrawParam, Rect, Transform},
+    graphics::{self, Canvas, Color, DrawParam, Image, Rect, Transform},
     mint::{self, Point2},
 };
 
-use super::{Animation, ImageCache, MAP_CORNER_X, MAP_CORNER_Y, TILE_SIZE};
-use crate::core::{Appearance, Map};
+use super::{Animation, ImageCache, SpriteDescription, SpriteSheets, MAP_CORNER_X, MAP_CORNER_Y, TILE_SIZE};
+use crate::core::{AnimationState, Appearance, Direction, Map};
 
-pub fn render_sprite(canvas: &mut Canvas, screen_position: Vec2, appearance: &Appearance, animation: &Animation, images: &ImageCache) {
-    let image = images.get(appearance.filename()).clone();
+pub fn render_sprite(
+    canvas: &mut Canvas,
+    screen_position: Vec2,
+    appearance: &Appearance,
+    animation: &Animation,
+    facing: Direction,
+    images: &ImageCache,
+    sheets: &SpriteSheets,
+) {
+    let sheet = match sheets.get(&appearance.kind) {
+        Some(sheet) => sheet,
+        None => {
+            images.warn(appearance.kind.name(), "no sprite sheet for");
+            let placeholder = images.placeholder();
+            let scale = TILE_SIZE / placeholder.width() as f32;
+            canvas.draw(
+                placeholder,
+                DrawParam::new()
+                    .dest(screen_position)
+                    .offset(Vec2::new(0.5, 0.5))
+                    .scale(Vec2::new(scale, scale)),
+            );
+            return;
+        }
+    };
     let animation_offset = animation.sprite.as_ref().map(|a| a.now() as usize).unwrap_or(0);
+    let (image, params) = sprite_draw_params(images, screen_position, sheet, appearance.state, animation_offset, facing);
+    canvas.draw(image, params);
+}
 
-    let (image_offset_x, image_offset_y) = appearance.sprite_rect(animation_offset);
-    let scale = appearance.sprite_scale();
-    let offset = appearance.sprite_offset();
+// The sheet's image along with how to draw the current frame of it
+pub fn sprite_draw_params<'a>(
+    images: &'a ImageCache,
+    screen_position: Vec2,
+    sheet: &SpriteDescription,
+    state: AnimationState,
+    animation_offset: usize,
+    facing: Direction,
+) -> (&'a Image, DrawParam) {
+    let image = images.get(&sheet.image);
+    let (image_offset_x, image_offset_y) = sheet.sprite_rect(state, animation_offset, facing);
+    let mut scale = Vec2::splat(sheet.sprite_scale());
+    let flip = if sheet.sprite_flipped(facing) { -1.0 } else { 1.0 };
+    let offset = sheet.sprite_offset();
     let render_position = screen_position + offset;
-    let sprite_size = appearance.sprite_size();
+    let sprite_size = sheet.sprite_size();
 
-    let draw_params = DrawParam {
-        src: Rect {
+    let src = if images.is_stand_in(image) {
+        // Stretched over the whole frame so it stands in at the sprite's size
+        scale *= Vec2::new(sprite_size.0 as f32 / image.width() as f32, sprite_size.1 as f32 / image.height() as f32);
+        Rect::one()
+    } else {
+        Rect {
             x: image_offset_x as f32 / image.width() as f32,
             y: image_offset_y as f32 / image.height() as f32,
             w: sprite_size.0 as f32 / image.width() as f32,
             h: sprite_size.1 as f32 / image.height() as f32,
-        },
+        }
+    };
+
+    let params = DrawParam {
+        src,
         transform: Transform::Values {
             rotation: 0.0,
-            scale: mint::Vector2 {
-                x: scale as f32,
-                y: scale as f32,
-            },
+            scale: mint::Vector2 { x: flip * scale.x, y: scale.y },
             offset: mint::Point2 { x: 0.5, y: 0.5 },
             dest: Point2 {
                 x: render_position.x,
@@ -39,8 +81,7 @@ pub fn render_sprite(canvas: &mut Canvas, screen_position: Vec2, appearance: &Ap
         },
         ..Default::default()
     };
-
-    canvas.draw(&image, draw_params);
+    (image, params)
 }
 
 pub fn draw_image(canvas: &mut Canvas, world: &mut World, image: &str, position: mint::Point2<f32>) {
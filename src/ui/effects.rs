@@ -0,0 +1,183 @@
+use bevy_ecs::prelude::*;
+use ggez::{
+    glam::Vec2,
+    graphics::{self, Canvas, Color, DrawParam},
+    mint::Vector2,
+    GameResult,
+};
+use keyframe::{functions::Linear, AnimationSequence};
+use keyframe_derive::CanTween;
+
+use crate::core::{AnimationState, AppearanceKind, Direction};
+
+use super::{screen_point_for_map_grid, sprite_draw_params, ImageCache, SpawnEffectEvent, SpriteSheets, TILE_SIZE};
+
+#[derive(Clone, Debug)]
+pub enum EffectKind {
+    // A landed projectile lingering for a moment before it is gone
+    ProjectileFade(AppearanceKind),
+    // A fallen character caught mid flinch as they fade away
+    DeathFade(AppearanceKind, Direction),
+    HitSpark,
+    MuzzleFlash,
+    SpellBurst,
+}
+
+#[derive(CanTween, Debug, Clone)]
+pub struct EffectFrame {
+    pub alpha: f32,
+    pub scale: f32,
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+}
+
+impl EffectFrame {
+    fn new(alpha: f32, scale: f32, color: (f32, f32, f32)) -> Self {
+        EffectFrame {
+            alpha,
+            scale,
+            red: color.0,
+            green: color.1,
+            blue: color.2,
+        }
+    }
+
+    fn color(&self) -> Color {
+        Color::new(self.red, self.green, self.blue, self.alpha)
+    }
+}
+
+impl Default for EffectFrame {
+    fn default() -> Self {
+        EffectFrame::new(1.0, 1.0, (1.0, 1.0, 1.0))
+    }
+}
+
+// Short lived visuals with no gameplay meaning, which remove themselves once their tween ends
+#[derive(Component)]
+pub struct Effect {
+    pub kind: EffectKind,
+    // In map coordinates, which can be in between grid cells
+    pub position: Vector2<f32>,
+    pub tween: AnimationSequence<EffectFrame>,
+}
+
+impl Effect {
+    pub fn new(kind: EffectKind, position: Vector2<f32>) -> Self {
+        Effect {
+            tween: kind.create_tween(),
+            kind,
+            position,
+        }
+    }
+}
+
+const SPARK_COLOR: (f32, f32, f32) = (1.0, 0.9, 0.5);
+const FLASH_COLOR: (f32, f32, f32) = (1.0, 0.75, 0.3);
+const BURST_COLOR: (f32, f32, f32) = (0.5, 0.6, 1.0);
+
+impl EffectKind {
+    fn create_tween(&self) -> AnimationSequence<EffectFrame> {
+        let (start, end, duration) = match self {
+            EffectKind::ProjectileFade(_) => (EffectFrame::new(1.0, 1.0, (1.0, 1.0, 1.0)), EffectFrame::new(0.0, 1.0, (1.0, 1.0, 1.0)), 12.0),
+            EffectKind::DeathFade(..) => (EffectFrame::new(1.0, 1.0, (1.0, 1.0, 1.0)), EffectFrame::new(0.0, 1.0, (1.0, 1.0, 1.0)), 40.0),
+            EffectKind::HitSpark => (EffectFrame::new(1.0, 0.2, SPARK_COLOR), EffectFrame::new(0.0, 0.6, SPARK_COLOR), 10.0),
+            EffectKind::MuzzleFlash => (EffectFrame::new(1.0, 0.4, FLASH_COLOR), EffectFrame::new(0.0, 0.1, FLASH_COLOR), 6.0),
+            EffectKind::SpellBurst => (EffectFrame::new(0.8, 0.2, BURST_COLOR), EffectFrame::new(0.0, 1.2, BURST_COLOR), 20.0),
+        };
+        AnimationSequence::from(vec![(start, 0.0, Linear).into(), (end, duration, Linear).into()])
+    }
+}
+
+// Sparks, flashes and bursts all share one circle, scaled and tinted per effect
+pub struct EffectMeshes {
+    circle: graphics::Mesh,
+}
+
+impl EffectMeshes {
+    pub fn new(ctx: &ggez::Context) -> GameResult<Self> {
+        let circle = graphics::Mesh::new_circle(ctx, graphics::DrawMode::fill(), Vec2::new(0.0, 0.0), TILE_SIZE / 2.0, 0.5, Color::WHITE)?;
+        Ok(EffectMeshes { circle })
+    }
+}
+
+#[no_mangle]
+pub fn start_effects(mut requests: EventReader<SpawnEffectEvent>, mut commands: Commands) {
+    for request in requests.iter() {
+        commands.spawn().insert(Effect::new(request.kind.clone(), request.position));
+    }
+}
+
+pub fn advance_effects(world: &mut World) {
+    let mut finished = vec![];
+    let mut query = world.query::<(Entity, &mut Effect)>();
+    for (entity, mut effect) in query.iter_mut(world) {
+        if effect.tween.advance_by(1.0) > 0.0 {
+            finished.push(entity);
+        }
+    }
+    for entity in finished {
+        world.despawn(entity);
+    }
+}
+
+pub fn draw_effects(world: &mut World, canvas: &mut Canvas) {
+    let mut query = world.query::<&Effect>();
+    let images = world.get_resource::<ImageCache>().unwrap();
+    let sheets = world.get_resource::<SpriteSheets>().unwrap();
+    let meshes = world.get_resource::<EffectMeshes>().unwrap();
+    for effect in query.iter(world) {
+        let frame = effect.tween.now();
+        let screen_position = screen_point_for_map_grid(effect.position.x, effect.position.y);
+        match &effect.kind {
+            // Sprites keep their own size and only fade
+            EffectKind::ProjectileFade(kind) => {
+                if let Some(sheet) = sheets.get(kind) {
+                    let (image, params) = sprite_draw_params(images, screen_position, sheet, AnimationState::Idle, 0, Direction::None);
+                    canvas.draw(image, params.color(frame.color()));
+                }
+            }
+            EffectKind::DeathFade(kind, facing) => {
+                if let Some(sheet) = sheets.get(kind) {
+                    let (image, params) = sprite_draw_params(images, screen_position, sheet, AnimationState::Hit, 0, *facing);
+                    canvas.draw(image, params.color(frame.color()));
+                }
+            }
+            EffectKind::HitSpark | EffectKind::MuzzleFlash | EffectKind::SpellBurst => {
+                let params = DrawParam::new()
+                    .dest(screen_position)
+                    .scale(Vec2::new(frame.scale, frame.scale))
+                    .color(frame.color());
+                canvas.draw(&meshes.circle, params);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finished_effects_removed() {
+        let mut world = World::new();
+        let position = Vector2 { x: 1.0, y: 1.0 };
+        let flash = world.spawn().insert(Effect::new(EffectKind::MuzzleFlash, position)).id();
+        let fade = world
+            .spawn()
+            .insert(Effect::new(EffectKind::DeathFade(AppearanceKind::Golem, Direction::None), position))
+            .id();
+
+        for _ in 0..10 {
+            advance_effects(&mut world);
+        }
+        assert!(world.get_entity(flash).is_none());
+        assert!(world.get_entity(fade).is_some());
+
+        for _ in 0..40 {
+            advance_effects(&mut world);
+        }
+        assert!(world.get_entity(fade).is_none());
+    }
+}
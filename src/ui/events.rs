@@ -3,6 +3,8 @@ use ggez::mint::Vector2;
 
 use crate::core::AnimationState;
 
+use super::EffectKind;
+
 pub struct SpriteAnimateActionEvent {
     pub entity: Entity,
     pub state: AnimationState,
@@ -47,3 +49,14 @@ impl MovementAnimationComplete {
         MovementAnimationComplete { entity }
     }
 }
+
+pub struct SpawnEffectEvent {
+    pub kind: EffectKind,
+    pub position: Vector2<f32>,
+}
+
+impl SpawnEffectEvent {
+    pub fn new(kind: EffectKind, position: Vector2<f32>) -> Self {
+        SpawnEffectEvent { kind, position }
+    }
+}
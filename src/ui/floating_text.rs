@@ -0,0 +1,147 @@
+use bevy_ecs::prelude::*;
+use ggez::{
+    graphics::{self, Canvas, Color, DrawParam, TextAlign, TextLayout},
+    mint::Vector2,
+};
+use keyframe::{functions::Linear, AnimationSequence};
+use keyframe_derive::CanTween;
+
+use crate::core::{CombatEvent, CombatEventKind};
+
+use super::screen_point_for_map_grid;
+
+#[derive(CanTween, Debug, Clone, Default)]
+pub struct FloatingTextFrame {
+    // In tiles above the anchor
+    pub rise: f32,
+    pub alpha: f32,
+}
+
+#[derive(Component)]
+pub struct FloatingText {
+    pub text: String,
+    pub color: Color,
+    // Map coordinates of the top middle of whoever it is about
+    pub anchor: Vector2<f32>,
+    // How many other texts were already rising from the same spot when this one started
+    pub stack: u32,
+    pub tween: AnimationSequence<FloatingTextFrame>,
+}
+
+const FLOATING_TEXT_DURATION: f32 = 50.0;
+const FLOATING_TEXT_RISE: f32 = 0.6;
+const FLOATING_TEXT_STACK_SPACING: f32 = 0.4;
+// Older texts have risen far enough that a new one will not overlap them
+const FLOATING_TEXT_STACK_WINDOW: f64 = 20.0;
+
+impl FloatingText {
+    pub fn new(text: String, color: Color, anchor: Vector2<f32>, stack: u32) -> Self {
+        let frames = vec![
+            (FloatingTextFrame { rise: 0.0, alpha: 1.0 }, 0.0, Linear).into(),
+            (
+                FloatingTextFrame {
+                    rise: FLOATING_TEXT_RISE,
+                    alpha: 0.0,
+                },
+                FLOATING_TEXT_DURATION,
+                Linear,
+            )
+                .into(),
+        ];
+        FloatingText {
+            text,
+            color,
+            anchor,
+            stack,
+            tween: AnimationSequence::from(frames),
+        }
+    }
+}
+
+fn describe(kind: CombatEventKind) -> (String, Color) {
+    match kind {
+        CombatEventKind::Damage { dealt, absorbed } if dealt == 0 && absorbed > 0 => ("Absorbed".to_string(), Color::new(0.5, 0.7, 1.0, 1.0)),
+        CombatEventKind::Damage { dealt, .. } => (format!("-{}", dealt), Color::new(0.95, 0.25, 0.2, 1.0)),
+        CombatEventKind::Heal(amount) => (format!("+{}", amount), Color::new(0.35, 0.9, 0.35, 1.0)),
+        CombatEventKind::Miss => ("Miss".to_string(), Color::new(0.8, 0.8, 0.8, 1.0)),
+        CombatEventKind::StatusApplied(kind) => (kind.name().to_string(), Color::new(0.95, 0.85, 0.3, 1.0)),
+    }
+}
+
+#[no_mangle]
+pub fn start_floating_text(mut events: EventReader<CombatEvent>, existing: Query<&FloatingText>, mut commands: Commands) {
+    let mut started: Vec<Vector2<f32>> = vec![];
+    for event in events.iter() {
+        let position = match event.position {
+            Some(position) => position,
+            None => continue,
+        };
+        let center = position.visual_center();
+        let anchor = Vector2 {
+            x: center.x,
+            y: center.y - position.height as f32 / 2.0,
+        };
+
+        // Multiple hits on the same character, or any of a large character's tiles, stack instead of overlapping
+        let rising = existing
+            .iter()
+            .filter(|t| t.anchor == anchor && t.tween.time() < FLOATING_TEXT_STACK_WINDOW)
+            .count();
+        let stack = rising + started.iter().filter(|a| **a == anchor).count();
+        started.push(anchor);
+
+        let (text, color) = describe(event.kind);
+        commands.spawn().insert(FloatingText::new(text, color, anchor, stack as u32));
+    }
+}
+
+pub fn advance_floating_text(world: &mut World) {
+    let mut finished = vec![];
+    let mut query = world.query::<(Entity, &mut FloatingText)>();
+    for (entity, mut text) in query.iter_mut(world) {
+        if text.tween.advance_by(1.0) > 0.0 {
+            finished.push(entity);
+        }
+    }
+    for entity in finished {
+        world.despawn(entity);
+    }
+}
+
+pub fn draw_floating_text(world: &mut World, canvas: &mut Canvas) {
+    for floating in world.query::<&FloatingText>().iter(world) {
+        let frame = floating.tween.now();
+        let rise = frame.rise + floating.stack as f32 * FLOATING_TEXT_STACK_SPACING;
+        let screen_position = screen_point_for_map_grid(floating.anchor.x, floating.anchor.y - rise);
+
+        let mut text = graphics::Text::new(floating.text.as_str());
+        text.set_font("default").set_scale(26.0).set_layout(TextLayout {
+            h_align: TextAlign::Middle,
+            v_align: TextAlign::End,
+        });
+        let color = Color::new(floating.color.r, floating.color.g, floating.color.b, frame.alpha);
+        canvas.draw(&text, DrawParam::new().dest(screen_position).color(color));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finished_text_removed() {
+        let mut world = World::new();
+        let text = FloatingText::new("-5".to_string(), Color::WHITE, Vector2 { x: 1.0, y: 1.0 }, 0);
+        let entity = world.spawn().insert(text).id();
+
+        for _ in 0..10 {
+            advance_floating_text(&mut world);
+        }
+        assert!(world.get_entity(entity).is_some());
+
+        for _ in 0..FLOATING_TEXT_DURATION as u32 {
+            advance_floating_text(&mut world);
+        }
+        assert!(world.get_entity(entity).is_none());
+    }
+}
@@ -1,6 +1,7 @@
 use std::path;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use bevy_ecs::prelude::*;
 use ggez::{
     event::EventHandler,
@@ -8,25 +9,35 @@ use ggez::{
     Context, GameError, GameResult,
 };
 
-use crate::core;
+use crate::core::{self, MapIndex, MapKind, MapRequest};
 
-use super::{ImageCache, SceneKind, Scenes, ScreenCoordinates};
+use super::{EffectMeshes, ImageCache, KeyBindings, SceneKind, Scenes, ScreenCoordinates, SpriteSheets};
+
+pub const DEFAULT_FONT_PATH: &str = "/fonts/LibreFranklin-Regular.ttf";
 
 pub struct GameState {
     world: World,
     schedule: Schedule,
+    #[cfg(feature = "hotreload")]
+    data_watcher: super::DataWatcher,
 }
 
 impl GameState {
-    pub fn new(ctx: &mut Context) -> Result<GameState> {
-        let mut world = core::create_game_world(&mut ctx.fs)?;
+    pub fn new(ctx: &mut Context, request: MapRequest) -> Result<GameState> {
+        let maps = discover_maps(&ctx.fs)?;
+        let mut world = create_world_on_open_map(ctx, &maps, request)?;
+        world.insert_resource(maps);
 
-        ctx.gfx
-            .add_font("default", graphics::FontData::from_path(ctx, "/fonts/LibreFranklin-Regular.ttf")?);
+        ctx.gfx.add_font("default", graphics::FontData::from_path(ctx, DEFAULT_FONT_PATH)?);
 
         world.insert_resource(ScreenCoordinates::calculate(ctx));
         world.insert_resource(ImageCache::load(ctx, path::PathBuf::from("/"))?);
+        world.insert_resource(KeyBindings::load(&ctx.fs)?);
+        world.insert_resource(SpriteSheets::load(&ctx.fs)?);
+        world.insert_resource(EffectMeshes::new(ctx)?);
         super::setup_ui_resources(&mut world);
+        super::report_missing_assets(&mut world);
+        super::preload_images(&mut world, ctx)?;
 
         let mut schedule = core::create_game_schedule();
         schedule.add_stage("ui", super::create_ui_schedule());
@@ -35,26 +46,88 @@ impl GameState {
         scenes.push(SceneKind::Battle);
         world.insert_resource(scenes);
 
-        Ok(GameState { world, schedule })
+        #[cfg(feature = "hotreload")]
+        let data_watcher = super::DataWatcher::new(&world);
+
+        Ok(GameState {
+            world,
+            schedule,
+            #[cfg(feature = "hotreload")]
+            data_watcher,
+        })
     }
 
     pub fn current_scene(&self) -> SceneKind {
         self.world.get_resource::<Scenes>().unwrap().current()
     }
+
+    fn fixed_update(&mut self) {
+        self.world.get_resource_mut::<core::Frame>().unwrap().current += 1;
+        super::advance_all_animations(&mut self.world);
+        self.schedule.run_once(&mut self.world);
+    }
+}
+
+// Each biome's folder holds its maps as map<variant>.dat
+fn discover_maps(fs: &ggez::filesystem::Filesystem) -> Result<MapIndex> {
+    let mut paths = vec![];
+    for kind in MapKind::ALL {
+        let folder = kind.folder_path();
+        if fs.is_dir(&folder) {
+            for item in fs.read_dir(&folder)? {
+                paths.push(item.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+    Ok(MapIndex::from_paths(&paths))
+}
+
+// Maps whose walls fall where the starting characters stand are passed over for the next choice
+fn create_world_on_open_map(ctx: &mut Context, maps: &MapIndex, request: MapRequest) -> Result<World> {
+    let choices = maps.choices(request, random_seed());
+    if choices.is_empty() {
+        return Err(anyhow!("No map found for {:?}", request));
+    }
+    for (kind, variant) in choices {
+        let mut world = core::create_game_world(&mut ctx.fs, kind, variant)?;
+        let blocked = core::find_blocked_spawns(&mut world).len();
+        if blocked == 0 {
+            return Ok(world);
+        }
+        eprintln!("Warning: skipping {:?} map {}, as {} character(s) would start on walls", kind, variant, blocked);
+    }
+    Err(anyhow!("No map for {:?} has room for the starting characters", request))
+}
+
+fn random_seed() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0)
 }
 
 const FPS: u32 = 60;
 
+// After a long stall (alt-tab, a closed laptop lid) only this many missed
+// updates are replayed instead of fast forwarding through all of them
+const MAX_CATCH_UP_UPDATES: u32 = 5;
+
 impl EventHandler for GameState {
     fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
+        let mut updates = 0;
         while ctx.time.check_update_time(FPS) {
-            self.schedule.run_once(&mut self.world);
+            if updates < MAX_CATCH_UP_UPDATES {
+                self.fixed_update();
+                updates += 1;
+            }
         }
         Scenes::update(self.current_scene(), &mut self.world, ctx);
+
+        #[cfg(feature = "hotreload")]
+        self.data_watcher.update(&mut self.world, ctx);
         Ok(())
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+        self.world.get_resource_mut::<ImageCache>().unwrap().update(ctx);
+
         let mut canvas = graphics::Canvas::from_frame(ctx, Color::BLACK);
         // Because pixel art
         canvas.set_sampler(graphics::Sampler::nearest_clamp());
@@ -0,0 +1,122 @@
+use std::io::Read;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use winit::event::VirtualKeyCode;
+
+use crate::core::Direction;
+
+// Optional overrides in the data folder, any direction left out keeps its defaults
+pub const KEY_BINDINGS_PATH: &str = "/config/keybindings.toml";
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub north: Vec<VirtualKeyCode>,
+    pub north_east: Vec<VirtualKeyCode>,
+    pub east: Vec<VirtualKeyCode>,
+    pub south_east: Vec<VirtualKeyCode>,
+    pub south: Vec<VirtualKeyCode>,
+    pub south_west: Vec<VirtualKeyCode>,
+    pub west: Vec<VirtualKeyCode>,
+    pub north_west: Vec<VirtualKeyCode>,
+}
+
+impl Default for KeyBindings {
+    // Arrows, numpad and vi-keys
+    fn default() -> Self {
+        KeyBindings {
+            north: vec![VirtualKeyCode::Up, VirtualKeyCode::Numpad8, VirtualKeyCode::K],
+            north_east: vec![VirtualKeyCode::Numpad9, VirtualKeyCode::U],
+            east: vec![VirtualKeyCode::Right, VirtualKeyCode::Numpad6, VirtualKeyCode::L],
+            south_east: vec![VirtualKeyCode::Numpad3, VirtualKeyCode::N],
+            south: vec![VirtualKeyCode::Down, VirtualKeyCode::Numpad2, VirtualKeyCode::J],
+            south_west: vec![VirtualKeyCode::Numpad1, VirtualKeyCode::B],
+            west: vec![VirtualKeyCode::Left, VirtualKeyCode::Numpad4, VirtualKeyCode::H],
+            north_west: vec![VirtualKeyCode::Numpad7, VirtualKeyCode::Y],
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn load(fs: &ggez::filesystem::Filesystem) -> Result<KeyBindings> {
+        if !fs.exists(KEY_BINDINGS_PATH) {
+            return Ok(KeyBindings::default());
+        }
+        let mut text = String::new();
+        fs.open(KEY_BINDINGS_PATH)?.read_to_string(&mut text)?;
+        KeyBindings::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<KeyBindings> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn movement_direction(&self, key: VirtualKeyCode) -> Option<Direction> {
+        [
+            (&self.north, Direction::North),
+            (&self.north_east, Direction::NorthEast),
+            (&self.east, Direction::East),
+            (&self.south_east, Direction::SouthEast),
+            (&self.south, Direction::South),
+            (&self.south_west, Direction::SouthWest),
+            (&self.west, Direction::West),
+            (&self.north_west, Direction::NorthWest),
+        ]
+        .iter()
+        .find(|(keys, _)| keys.contains(&key))
+        .map(|(_, direction)| *direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_bindings() {
+        let bindings = KeyBindings::default();
+        let expected = [
+            (Direction::North, [VirtualKeyCode::Up, VirtualKeyCode::Numpad8, VirtualKeyCode::K]),
+            (Direction::East, [VirtualKeyCode::Right, VirtualKeyCode::Numpad6, VirtualKeyCode::L]),
+            (Direction::South, [VirtualKeyCode::Down, VirtualKeyCode::Numpad2, VirtualKeyCode::J]),
+            (Direction::West, [VirtualKeyCode::Left, VirtualKeyCode::Numpad4, VirtualKeyCode::H]),
+        ];
+        for (direction, keys) in expected {
+            for key in keys {
+                assert_eq!(Some(direction), bindings.movement_direction(key));
+            }
+        }
+        // Diagonals have no arrow key
+        let diagonals = [
+            (Direction::NorthEast, [VirtualKeyCode::Numpad9, VirtualKeyCode::U]),
+            (Direction::SouthEast, [VirtualKeyCode::Numpad3, VirtualKeyCode::N]),
+            (Direction::SouthWest, [VirtualKeyCode::Numpad1, VirtualKeyCode::B]),
+            (Direction::NorthWest, [VirtualKeyCode::Numpad7, VirtualKeyCode::Y]),
+        ];
+        for (direction, keys) in diagonals {
+            for key in keys {
+                assert_eq!(Some(direction), bindings.movement_direction(key));
+            }
+        }
+        assert_eq!(None, bindings.movement_direction(VirtualKeyCode::Space));
+    }
+
+    #[test]
+    fn partial_override() {
+        let bindings = KeyBindings::parse("north = [\"W\"]").unwrap();
+        assert_eq!(Some(Direction::North), bindings.movement_direction(VirtualKeyCode::W));
+        assert_eq!(None, bindings.movement_direction(VirtualKeyCode::Up));
+        assert_eq!(None, bindings.movement_direction(VirtualKeyCode::K));
+        // Everything left out keeps its defaults
+        assert_eq!(Some(Direction::South), bindings.movement_direction(VirtualKeyCode::Down));
+        assert_eq!(Some(Direction::NorthWest), bindings.movement_direction(VirtualKeyCode::Y));
+    }
+
+    #[test]
+    fn malformed_bindings() {
+        assert!(KeyBindings::parse("north = [\"NotAKey\"]").is_err());
+        assert!(KeyBindings::parse("north = \"W\"").is_err());
+        assert!(KeyBindings::parse("north = [").is_err());
+    }
+}
@@ -1,6 +1,6 @@
 mod game_state;
 use bevy_ecs::prelude::*;
-pub use game_state::GameState;
+pub use game_state::{GameState, DEFAULT_FONT_PATH};
 
 mod utils;
 pub use utils::*;
@@ -26,6 +26,23 @@ pub use schedule::*;
 mod events;
 pub use events::*;
 
+mod key_bindings;
+pub use key_bindings::*;
+
+mod effects;
+pub use effects::*;
+
+mod floating_text;
+pub use floating_text::*;
+
+mod asset_report;
+pub use asset_report::*;
+
+#[cfg(feature = "hotreload")]
+mod data_watcher;
+#[cfg(feature = "hotreload")]
+pub use data_watcher::*;
+
 pub const GAME_WIDTH: f32 = 1280.0;
 pub const GAME_HEIGHT: f32 = 960.0;
 
@@ -38,6 +55,7 @@ pub fn setup_ui_resources(world: &mut World) {
     world.insert_resource(Events::<SpriteAnimateActionCompleteEvent>::default());
     world.insert_resource(Events::<MovementAnimationEvent>::default());
     world.insert_resource(Events::<MovementAnimationComplete>::default());
+    world.insert_resource(Events::<SpawnEffectEvent>::default());
 }
 
 // Since we aren't using Bevy's App model, we have to clear our event buffers by hand
@@ -46,9 +64,11 @@ pub fn clear_event_buffers(
     mut b: ResMut<Events<SpriteAnimateActionCompleteEvent>>,
     mut c: ResMut<Events<MovementAnimationEvent>>,
     mut d: ResMut<Events<MovementAnimationComplete>>,
+    mut e: ResMut<Events<SpawnEffectEvent>>,
 ) {
     a.update();
     b.update();
     c.update();
     d.update();
+    e.update();
 }
@@ -98,15 +98,15 @@ impl Scenes {
 
     pub fn mouse_button_up_event(state: SceneKind, world: &mut World, ctx: &mut ggez::Context, button: ggez::event::MouseButton, x: f32, y: f32) {
         match state {
-            SceneKind::Battle => {}
+            SceneKind::Battle => battle_mouse_button_up_event(world, ctx, button, x, y),
             SceneKind::DebugOverlay => debug_mouse_button_up_event(world, ctx, button, x, y),
             SceneKind::Target => targeting_mouse_button_up_event(world, ctx, button, x, y),
         }
     }
 
-    pub fn mouse_motion_event(state: SceneKind, _world: &mut World, _ctx: &mut ggez::Context, _x: f32, _y: f32, _dx: f32, _dy: f32) {
+    pub fn mouse_motion_event(state: SceneKind, world: &mut World, ctx: &mut ggez::Context, x: f32, y: f32, dx: f32, dy: f32) {
         match state {
-            SceneKind::Battle => {}
+            SceneKind::Battle => battle_mouse_motion_event(world, ctx, x, y, dx, dy),
             SceneKind::DebugOverlay => {}
             SceneKind::Target => {}
         }
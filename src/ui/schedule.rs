@@ -1,7 +1,9 @@
 use bevy_ecs::schedule::SystemStage;
 
 #[cfg(not(feature = "hotreload"))]
-use super::{end_movement_animation, end_sprite_animation, start_movement_animations, start_sprite_animations};
+use super::{
+    end_movement_animation, end_sprite_animation, start_effects, start_floating_text, start_movement_animations, start_sprite_animations, update_input_gate,
+};
 
 #[cfg(feature = "hotreload")]
 use systems_hot::*;
@@ -9,13 +11,16 @@ use systems_hot::*;
 #[cfg(feature = "hotreload")]
 #[hot_lib_reloader::hot_module(dylib = "arenalib")]
 mod systems_hot {
-    use crate::core::Appearance;
+    use crate::core::{Appearance, Character, CombatEvent, InputGate};
     use crate::ui::{
-        Animation, MovementAnimationComplete, MovementAnimationEvent, PostMovementAction, SpriteAnimateActionCompleteEvent, SpriteAnimateActionEvent,
+        Animation, FloatingText, MovementAnimationComplete, MovementAnimationEvent, PostMovementAction, SpawnEffectEvent, SpriteAnimateActionCompleteEvent,
+        SpriteAnimateActionEvent, SpriteSheets,
     };
 
     use bevy_ecs::prelude::*;
     hot_functions_from_file!("src/ui/animation.rs");
+    hot_functions_from_file!("src/ui/effects.rs");
+    hot_functions_from_file!("src/ui/floating_text.rs");
 }
 
 pub fn create_ui_schedule() -> SystemStage {
@@ -25,5 +30,8 @@ pub fn create_ui_schedule() -> SystemStage {
         .with_system(end_sprite_animation)
         .with_system(start_movement_animations)
         .with_system(end_movement_animation)
+        .with_system(update_input_gate)
+        .with_system(start_effects)
+        .with_system(start_floating_text)
         .with_system(super::clear_event_buffers) // This is is fine never to hot reload, it's data centric
 }
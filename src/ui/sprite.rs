@@ -1,171 +1,273 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::Read;
+
+use anyhow::{anyhow, Result};
 use ggez::glam::Vec2;
 use keyframe::{functions::Step, AnimationSequence, Keyframe};
+use serde::{
+    de::{DeserializeOwned, Error},
+    Deserialize, Deserializer, Serialize,
+};
 
-use crate::core::{AnimationState, Appearance, AppearanceKind};
+use crate::core::{AnimationState, AppearanceKind, Direction};
 
-use super::BackingImage;
+// Built in descriptions, which the optional file in the data folder adds to or replaces
+const DEFAULT_SPRITES: &str = include_str!("sprites.toml");
+pub const SPRITES_PATH: &str = "/images/sprites.toml";
 
-enum SpriteSize {
-    Detailed,
-    LargeEnemy,
-    Bolt,
+fn default_scale() -> f32 {
+    1.0
 }
 
-impl BackingImage for AppearanceKind {
-    fn filename(&self) -> &str {
-        match self {
-            AppearanceKind::MaleBrownHairBlueBody => "/images/battle/1_1.png",
-            AppearanceKind::Golem => "/images/monsters/$monster_golem1.png",
-            AppearanceKind::FireBolt => "/images/bolts/fire.png",
-        }
-    }
+// Toml table keys are always strings, so tables keyed by an enum are read as strings first
+fn enum_keyed<'de, D, K>(deserializer: D) -> Result<HashMap<K, usize>, D::Error>
+where
+    D: Deserializer<'de>,
+    K: DeserializeOwned + Hash + Eq,
+{
+    let raw = HashMap::<String, usize>::deserialize(deserializer)?;
+    raw.into_iter()
+        .map(|(key, value)| match toml::Value::String(key.clone()).try_into::<K>() {
+            Ok(key) => Ok((key, value)),
+            Err(_) => Err(D::Error::custom(format!("unknown variant {}", key))),
+        })
+        .collect()
 }
 
-impl Appearance {
-    pub fn filename(&self) -> &str {
-        self.kind.filename()
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SpriteDescription {
+    pub appearance: AppearanceKind,
+    pub image: String,
+    pub frame_width: usize,
+    pub frame_height: usize,
+    // Frames in each row of the sheet
+    pub columns: usize,
+    // Frames in each animation
+    pub frames: usize,
+    // The sheet position of each animation frame, when not simply in order
+    #[serde(default)]
+    pub frame_order: Vec<usize>,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    #[serde(default)]
+    pub offset: (f32, f32),
+    #[serde(default)]
+    pub mirror_east: bool,
+    // First frame of each animation state, with anything missing falling back to Idle
+    #[serde(default, deserialize_with = "enum_keyed")]
+    pub states: HashMap<AnimationState, usize>,
+    // First frame of each facing, for sheets that draw more than one
+    #[serde(default, deserialize_with = "enum_keyed")]
+    pub facings: HashMap<Direction, usize>,
+}
+
+impl SpriteDescription {
+    pub fn sprite_rect(&self, state: AnimationState, animation_offset: usize, facing: Direction) -> (usize, usize) {
+        let frame = self.frame_order.get(animation_offset).copied().unwrap_or(animation_offset);
+        let index = self.state_start(state) + self.facing_start(facing) + frame;
+        let row = index / self.columns;
+        let col = index % self.columns;
+        (self.frame_width * col, self.frame_height * row)
     }
 
-    const IDLE_ANIMATION_LENGTH: f32 = 140.0 / 3.0;
-    pub fn create_idle_animation(&self) -> AnimationSequence<f32> {
-        self.create_animation(Appearance::IDLE_ANIMATION_LENGTH)
+    fn state_start(&self, state: AnimationState) -> usize {
+        self.states.get(&state).or_else(|| self.states.get(&AnimationState::Idle)).copied().unwrap_or(0)
     }
 
-    const DEFAULT_ANIMATION_LENGTH: f32 = 120.0 / 3.0;
-    pub fn create_standard_sprite_animation(&self) -> AnimationSequence<f32> {
-        self.create_animation(Appearance::DEFAULT_ANIMATION_LENGTH)
+    // Diagonals use the side they lean towards, as sheets rarely draw them
+    fn facing_start(&self, facing: Direction) -> usize {
+        let mut candidates = vec![facing];
+        if let Some((vertical, horizontal)) = facing.split_diagonal() {
+            candidates.push(horizontal);
+            candidates.push(vertical);
+        }
+        candidates.push(Direction::South);
+        candidates.iter().find_map(|d| self.facings.get(d)).copied().unwrap_or(0)
     }
 
-    fn create_animation(&self, animation_length: f32) -> AnimationSequence<f32> {
-        let frames: Vec<Keyframe<f32>> = (0..self.sprite_animation_length())
-            .map(|i| (i as f32, i as f32 * animation_length, Step).into())
-            .collect();
-        AnimationSequence::from(frames)
+    pub fn sprite_scale(&self) -> f32 {
+        self.scale
     }
 
-    pub fn sprite_rect(&self, animation_offset: usize) -> (usize, usize) {
-        let index = self.sprite_index(animation_offset);
-        let sheet_size = self.sprite_sheet_size();
-        let row = index / sheet_size;
-        let col = index % sheet_size;
+    pub fn sprite_offset(&self) -> Vec2 {
+        self.offset.into()
+    }
 
-        let (width, height) = self.sprite_size();
-        (width * col, height * row)
+    pub fn sprite_size(&self) -> (usize, usize) {
+        (self.frame_width, self.frame_height)
     }
 
-    pub fn sprite_scale(&self) -> f32 {
-        match self.sprite_size_class() {
-            SpriteSize::Detailed => 0.65,
-            SpriteSize::LargeEnemy => self.large_enemy_size_class().scale(),
-            SpriteSize::Bolt => 1.5,
-        }
+    pub fn sprite_flipped(&self, facing: Direction) -> bool {
+        self.mirror_east && matches!(facing, Direction::East | Direction::NorthEast | Direction::SouthEast)
     }
+}
 
-    pub fn sprite_offset(&self) -> Vec2 {
-        match self.sprite_size_class() {
-            SpriteSize::Detailed => (0.0, -17.0).into(),
-            SpriteSize::LargeEnemy => self.large_enemy_size_class().offset(),
-            SpriteSize::Bolt => (0.0, -8.0).into(),
+#[derive(Deserialize)]
+struct SpriteDescriptionFile {
+    sprite: Vec<SpriteDescription>,
+}
+
+pub struct SpriteSheets {
+    sheets: HashMap<AppearanceKind, SpriteDescription>,
+}
+
+impl SpriteSheets {
+    pub fn load(fs: &ggez::filesystem::Filesystem) -> Result<SpriteSheets> {
+        if !fs.exists(SPRITES_PATH) {
+            return SpriteSheets::parse(None);
         }
+        let mut text = String::new();
+        fs.open(SPRITES_PATH)?.read_to_string(&mut text)?;
+        SpriteSheets::parse(Some(&text))
     }
 
-    pub fn sprite_size(&self) -> (usize, usize) {
-        match self.sprite_size_class() {
-            SpriteSize::Detailed => (144, 144),
-            SpriteSize::LargeEnemy => match self.large_enemy_size_class() {
-                LargeEnemySize::Normal => (94, 100),
-                LargeEnemySize::Bird => (122, 96),
-                LargeEnemySize::LargeBird => (122, 96),
-            },
-            SpriteSize::Bolt => (64, 64),
+    pub fn parse(overrides: Option<&str>) -> Result<SpriteSheets> {
+        let mut sheets = SpriteSheets { sheets: HashMap::new() };
+        sheets.add_from_str(DEFAULT_SPRITES)?;
+        if let Some(overrides) = overrides {
+            sheets.add_from_str(overrides)?;
         }
+        Ok(sheets)
     }
 
-    fn sprite_index(&self, animation_offset: usize) -> usize {
-        match self.sprite_size_class() {
-            SpriteSize::Detailed => {
-                // The detailed character sheets are somewhat strangely laid out
-                // 1, 2, 0
-                let offset = match animation_offset {
-                    0 => 2,
-                    1 => 0,
-                    2 => 1,
-                    _ => panic!("Unexpected animation offset"),
-                };
-
-                let index_base = match self.state {
-                    AnimationState::Idle => 0,
-                    AnimationState::AttackOne => 3,
-                    AnimationState::Walk => 6,
-                    AnimationState::AttackTwo => 12,
-                    AnimationState::Cheer => 15,
-                    AnimationState::Magic => 18,
-                    AnimationState::Bow => 21,
-                    AnimationState::Crouch => 24,
-                    AnimationState::Hit => 36,
-                    AnimationState::Status => 42,
-                    AnimationState::Item => 48,
-                };
-                index_base + offset
+    fn add_from_str(&mut self, text: &str) -> Result<()> {
+        let file: SpriteDescriptionFile = toml::from_str(text)?;
+        for sprite in file.sprite {
+            if sprite.columns == 0 {
+                return Err(anyhow!("Sprite sheet for {} needs at least one column", sprite.appearance.name()));
             }
-            SpriteSize::LargeEnemy => animation_offset,
-            SpriteSize::Bolt => match self.kind {
-                AppearanceKind::FireBolt => animation_offset,
-                _ => panic!("Unexpected bolt kind"),
-            },
+            self.sheets.insert(sprite.appearance.clone(), sprite);
         }
+        Ok(())
     }
 
-    fn sprite_sheet_size(&self) -> usize {
-        match self.sprite_size_class() {
-            SpriteSize::Detailed => 9,
-            SpriteSize::LargeEnemy => 3,
-            SpriteSize::Bolt => match self.kind {
-                AppearanceKind::FireBolt => 5,
-                _ => panic!("Unexpected bolt kind"),
-            },
-        }
+    pub fn get(&self, kind: &AppearanceKind) -> Option<&SpriteDescription> {
+        self.sheets.get(kind)
     }
 
-    fn large_enemy_size_class(&self) -> LargeEnemySize {
-        match self.kind {
-            AppearanceKind::Golem => LargeEnemySize::Normal,
-            _ => panic!("Unset large enemy size"),
-        }
+    pub fn iter(&self) -> impl Iterator<Item = &SpriteDescription> {
+        self.sheets.values()
     }
 
-    fn sprite_size_class(&self) -> SpriteSize {
-        match self.kind {
-            AppearanceKind::MaleBrownHairBlueBody => SpriteSize::Detailed,
-            AppearanceKind::Golem => SpriteSize::LargeEnemy,
-            AppearanceKind::FireBolt => SpriteSize::Bolt,
-        }
+    const IDLE_ANIMATION_LENGTH: f32 = 140.0 / 3.0;
+    pub fn create_idle_animation(&self, kind: &AppearanceKind) -> AnimationSequence<f32> {
+        self.create_animation(kind, SpriteSheets::IDLE_ANIMATION_LENGTH)
     }
 
-    fn sprite_animation_length(&self) -> usize {
-        3
+    const WALK_ANIMATION_LENGTH: f32 = 30.0 / 3.0;
+    pub fn create_walk_animation(&self, kind: &AppearanceKind) -> AnimationSequence<f32> {
+        self.create_animation(kind, SpriteSheets::WALK_ANIMATION_LENGTH)
     }
-}
 
-#[allow(dead_code)]
-enum LargeEnemySize {
-    Normal,
-    Bird,
-    LargeBird,
+    const DEFAULT_ANIMATION_LENGTH: f32 = 120.0 / 3.0;
+    pub fn create_standard_sprite_animation(&self, kind: &AppearanceKind) -> AnimationSequence<f32> {
+        self.create_animation(kind, SpriteSheets::DEFAULT_ANIMATION_LENGTH)
+    }
+
+    fn create_animation(&self, kind: &AppearanceKind, animation_length: f32) -> AnimationSequence<f32> {
+        let frame_count = self.get(kind).map(|s| s.frames).unwrap_or(1);
+        let frames: Vec<Keyframe<f32>> = (0..frame_count).map(|i| (i as f32, i as f32 * animation_length, Step).into()).collect();
+        AnimationSequence::from(frames)
+    }
 }
 
-impl LargeEnemySize {
-    fn scale(&self) -> f32 {
-        match self {
-            LargeEnemySize::LargeBird => 1.5,
-            _ => 1.0,
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn built_in(kind: AppearanceKind) -> SpriteDescription {
+        SpriteSheets::parse(None).unwrap().get(&kind).unwrap().clone()
     }
 
-    fn offset(&self) -> Vec2 {
-        match self {
-            LargeEnemySize::Normal => (0.0, 0.0).into(),
-            LargeEnemySize::Bird | LargeEnemySize::LargeBird => (1.0, -20.0).into(),
-        }
+    // These match the tables the sheets were drawn with before they moved to sprites.toml
+    #[test]
+    fn detailed_character_rects() {
+        let sprite = built_in(AppearanceKind::MaleBrownHairBlueBody);
+        assert_eq!((288, 0), sprite.sprite_rect(AnimationState::Idle, 0, Direction::South));
+        assert_eq!((0, 0), sprite.sprite_rect(AnimationState::Idle, 1, Direction::South));
+        assert_eq!((144, 0), sprite.sprite_rect(AnimationState::Idle, 2, Direction::South));
+        assert_eq!((1152, 0), sprite.sprite_rect(AnimationState::Walk, 0, Direction::West));
+        assert_eq!((288, 576), sprite.sprite_rect(AnimationState::Hit, 0, Direction::North));
+        assert_eq!((432, 720), sprite.sprite_rect(AnimationState::Item, 1, Direction::South));
+        assert_eq!((144, 144), sprite.sprite_size());
+        assert_eq!(0.65, sprite.sprite_scale());
+        assert_eq!(Vec2::new(0.0, -17.0), sprite.sprite_offset());
+        assert!(sprite.sprite_flipped(Direction::East));
+        assert!(sprite.sprite_flipped(Direction::NorthEast));
+        assert!(sprite.sprite_flipped(Direction::SouthEast));
+        assert!(!sprite.sprite_flipped(Direction::West));
+        assert!(!sprite.sprite_flipped(Direction::South));
+    }
+
+    #[test]
+    fn large_enemy_facing_rows() {
+        let sprite = built_in(AppearanceKind::Golem);
+        let row = |facing| sprite.sprite_rect(AnimationState::Idle, 1, facing);
+        assert_eq!((94, 0), row(Direction::None));
+        assert_eq!((94, 0), row(Direction::South));
+        assert_eq!((94, 100), row(Direction::West));
+        assert_eq!((94, 100), row(Direction::NorthWest));
+        assert_eq!((94, 100), row(Direction::SouthWest));
+        assert_eq!((94, 200), row(Direction::East));
+        assert_eq!((94, 200), row(Direction::NorthEast));
+        assert_eq!((94, 200), row(Direction::SouthEast));
+        assert_eq!((94, 300), row(Direction::North));
+        // Monsters have no other states, so everything plays their idle frames
+        assert_eq!((188, 100), sprite.sprite_rect(AnimationState::Magic, 2, Direction::West));
+        assert_eq!((94, 100), sprite.sprite_size());
+        assert_eq!(1.0, sprite.sprite_scale());
+        assert_eq!(Vec2::new(0.0, 0.0), sprite.sprite_offset());
+        assert!(!sprite.sprite_flipped(Direction::East));
+    }
+
+    #[test]
+    fn bolt_rects() {
+        let sprite = built_in(AppearanceKind::FireBolt);
+        assert_eq!((0, 0), sprite.sprite_rect(AnimationState::Idle, 0, Direction::East));
+        assert_eq!((128, 0), sprite.sprite_rect(AnimationState::Idle, 2, Direction::North));
+        assert_eq!((64, 64), sprite.sprite_size());
+        assert_eq!(1.5, sprite.sprite_scale());
+        assert_eq!(Vec2::new(0.0, -8.0), sprite.sprite_offset());
+    }
+
+    #[test]
+    fn overrides_replace_built_in() {
+        let overrides = r#"
+            [[sprite]]
+            appearance = "FireBolt"
+            image = "/images/bolts/ice.png"
+            frame_width = 32
+            frame_height = 32
+            columns = 4
+            frames = 4
+        "#;
+        let sheets = SpriteSheets::parse(Some(overrides)).unwrap();
+        let bolt = sheets.get(&AppearanceKind::FireBolt).unwrap();
+        assert_eq!("/images/bolts/ice.png", bolt.image);
+        assert_eq!((0, 32), bolt.sprite_rect(AnimationState::Idle, 4, Direction::East));
+        assert!(sheets.get(&AppearanceKind::Golem).is_some());
+    }
+
+    #[test]
+    fn data_defined_appearance() {
+        let overrides = r#"
+            [[sprite]]
+            appearance = "Bird"
+            image = "/images/monsters/bird.png"
+            frame_width = 122
+            frame_height = 96
+            columns = 3
+            frames = 3
+            offset = [1.0, -20.0]
+
+            [sprite.facings]
+            South = 0
+            West = 3
+        "#;
+        let sheets = SpriteSheets::parse(Some(overrides)).unwrap();
+        let bird = sheets.get(&AppearanceKind::Custom("Bird".to_string())).unwrap();
+        assert_eq!((244, 96), bird.sprite_rect(AnimationState::Idle, 2, Direction::NorthWest));
+        assert_eq!(Vec2::new(1.0, -20.0), bird.sprite_offset());
     }
 }
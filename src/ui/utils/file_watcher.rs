@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+// Polls files in the data folder for changes, as artists and designers save them while the game runs
+pub struct FileWatcher {
+    root: Option<PathBuf>,
+    // None for files that did not exist, so creating one counts as a change
+    modified: HashMap<String, Option<SystemTime>>,
+    frames_until_check: u32,
+}
+
+impl FileWatcher {
+    // Checking every file each frame would be wasteful
+    const CHECK_INTERVAL: u32 = 30;
+
+    pub fn new() -> Self {
+        FileWatcher {
+            root: crate::data_directory(),
+            modified: HashMap::new(),
+            frames_until_check: FileWatcher::CHECK_INTERVAL,
+        }
+    }
+
+    fn last_modified(&self, name: &str) -> Option<SystemTime> {
+        let path = self.root.as_ref()?.join(name.trim_start_matches('/'));
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    pub fn watch(&mut self, name: &str) {
+        self.modified.insert(name.to_string(), self.last_modified(name));
+    }
+
+    // Called every frame, only looks at the files every so often
+    pub fn changed(&mut self) -> Vec<String> {
+        self.frames_until_check -= 1;
+        if self.frames_until_check > 0 {
+            return vec![];
+        }
+        self.frames_until_check = FileWatcher::CHECK_INTERVAL;
+
+        let mut changed = vec![];
+        for name in self.modified.keys() {
+            if self.last_modified(name) != self.modified[name] {
+                changed.push(name.clone());
+            }
+        }
+        for name in &changed {
+            self.watch(name);
+        }
+        changed
+    }
+}
@@ -1,42 +1,170 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
+use std::io::Read;
 use std::path::PathBuf;
+use std::sync::Mutex;
 
 use anyhow::Result;
-use ggez::graphics::Image;
+use ggez::graphics::{Image, ImageFormat};
 use ggez::Context;
+use serde::Deserialize;
+
+// Names with no image behind them, such as a skill nobody has drawn an icon for yet
+pub const MISSING_IMAGE: &str = "/missing.png";
+
+// Optional list of images to decode at startup, on top of the current map
+pub const PRELOAD_PATH: &str = "/images/preload.toml";
+
+const PLACEHOLDER_SIZE: u32 = 32;
+const PLACEHOLDER_CHECK: u32 = 8;
+
+#[derive(Deserialize)]
+struct PreloadList {
+    images: Vec<String>,
+}
+
+pub fn parse_preload_list(text: &str) -> Result<Vec<String>> {
+    let list: PreloadList = toml::from_str(text)?;
+    Ok(list.images)
+}
+
+pub fn read_preload_list(fs: &ggez::filesystem::Filesystem) -> Result<Vec<String>> {
+    if !fs.exists(PRELOAD_PATH) {
+        return Ok(vec![]);
+    }
+    let mut text = String::new();
+    fs.open(PRELOAD_PATH)?.read_to_string(&mut text)?;
+    parse_preload_list(&text)
+}
 
 pub struct ImageCache {
-    images: HashMap<String, ggez::graphics::Image>,
+    // Every png in the data, found at startup without decoding any of them
+    known: HashSet<String>,
+    images: HashMap<String, Image>,
+    // Asked for while drawing before being decoded, which happens before the next frame
+    pending: Mutex<HashSet<String>>,
+    placeholder: Image,
+    // Drawn for the single frame before a pending image is decoded, as it is not actually missing
+    blank: Image,
+    // Each missing image is only warned about once, not every frame it is drawn
+    warned: Mutex<HashSet<String>>,
+    #[cfg(feature = "hotreload")]
+    watcher: super::FileWatcher,
 }
 
 impl ImageCache {
     pub fn load(ctx: &mut Context, dir: PathBuf) -> Result<Self> {
-        let mut images = HashMap::new();
-        load_images(ctx, dir, &mut images)?;
-        Ok(ImageCache { images })
+        let mut known = HashSet::new();
+        find_images(ctx, dir, &mut known)?;
+        Ok(ImageCache {
+            known,
+            images: HashMap::new(),
+            pending: Mutex::new(HashSet::new()),
+            placeholder: create_placeholder(ctx),
+            blank: Image::from_pixels(ctx, &[0, 0, 0, 0], ImageFormat::Rgba8UnormSrgb, 1, 1),
+            warned: Mutex::new(HashSet::new()),
+            #[cfg(feature = "hotreload")]
+            watcher: super::FileWatcher::new(),
+        })
     }
 
+    // An image is decoded the first time it is drawn, showing nothing until the next frame
     pub fn get(&self, name: &str) -> &Image {
-        match self.images.get(name) {
-            Some(image) => image,
-            None => panic!("Unable to find image {} in cache", name),
+        if let Some(image) = self.images.get(name) {
+            return image;
+        }
+        if self.known.contains(name) {
+            self.pending.lock().unwrap().insert(name.to_string());
+            return &self.blank;
+        }
+        self.warn(name, "unable to find image");
+        &self.placeholder
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.known.contains(name)
+    }
+
+    pub fn placeholder(&self) -> &Image {
+        &self.placeholder
+    }
+
+    // Stand ins are drawn whole, as any rect within them is meaningless
+    pub fn is_stand_in(&self, image: &Image) -> bool {
+        std::ptr::eq(image, &self.placeholder) || std::ptr::eq(image, &self.blank)
+    }
+
+    pub fn preload(&mut self, ctx: &mut Context, names: &[String]) {
+        for name in names {
+            if self.known.contains(name) && !self.images.contains_key(name) {
+                self.load_image(ctx, name);
+            }
+        }
+    }
+
+    // Called once a frame before drawing, as decoding needs the context
+    pub fn update(&mut self, ctx: &mut Context) {
+        let pending: Vec<String> = self.pending.lock().unwrap().drain().collect();
+        for name in pending {
+            self.load_image(ctx, &name);
+        }
+
+        // Lets artists see a saved png in game without restarting, like code hot reload
+        #[cfg(feature = "hotreload")]
+        for name in self.watcher.changed() {
+            self.load_image(ctx, &name);
+        }
+    }
+
+    fn load_image(&mut self, ctx: &mut Context, name: &str) {
+        match Image::from_path(ctx, name) {
+            Ok(image) => {
+                self.images.insert(name.to_string(), image);
+                #[cfg(feature = "hotreload")]
+                self.watcher.watch(name);
+            }
+            Err(e) => {
+                // Not retried every frame, the placeholder stays until it is fixed
+                self.known.remove(name);
+                self.warn(name, &format!("unable to load image ({})", e));
+            }
+        }
+    }
+
+    // Only the first time for each name, as drawing repeats every frame
+    pub fn warn(&self, name: &str, problem: &str) {
+        if self.warned.lock().unwrap().insert(name.to_string()) {
+            eprintln!("Warning: {} {}, drawing a placeholder", problem, name);
+        }
+    }
+}
+
+// A magenta and black checkerboard, which is hard to miss while playtesting
+fn create_placeholder(ctx: &Context) -> Image {
+    let mut pixels = Vec::with_capacity((PLACEHOLDER_SIZE * PLACEHOLDER_SIZE * 4) as usize);
+    for y in 0..PLACEHOLDER_SIZE {
+        for x in 0..PLACEHOLDER_SIZE {
+            if (x / PLACEHOLDER_CHECK + y / PLACEHOLDER_CHECK) % 2 == 0 {
+                pixels.extend_from_slice(&[255, 0, 255, 255]);
+            } else {
+                pixels.extend_from_slice(&[0, 0, 0, 255]);
+            }
         }
     }
+    Image::from_pixels(ctx, &pixels, ImageFormat::Rgba8UnormSrgb, PLACEHOLDER_SIZE, PLACEHOLDER_SIZE)
 }
 
-fn load_images(ctx: &mut Context, dir: PathBuf, images: &mut HashMap<String, ggez::graphics::Image>) -> Result<()> {
+fn find_images(ctx: &mut Context, dir: PathBuf, known: &mut HashSet<String>) -> Result<()> {
     for item in ctx.fs.read_dir(dir)? {
         if ctx.fs.is_file(&item) {
             if let Some(extension) = item.extension().and_then(OsStr::to_str).map(|s| s.to_lowercase()) {
                 if extension.as_str() == "png" {
-                    let image = ggez::graphics::Image::from_path(ctx, &item)?;
                     let key = item.to_str().unwrap().replace('\\', "/");
-                    images.insert(key, image);
+                    known.insert(key);
                 }
             }
         } else {
-            load_images(ctx, item, images)?;
+            find_images(ctx, item, known)?;
         }
     }
     Ok(())
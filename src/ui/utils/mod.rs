@@ -1,3 +1,8 @@
+#[cfg(feature = "hotreload")]
+mod file_watcher;
+#[cfg(feature = "hotreload")]
+pub use file_watcher::*;
+
 mod image_cache;
 pub use image_cache::*;
 
@@ -0,0 +1,368 @@
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use bevy_ecs::prelude::*;
+
+use crate::core::{create_game_world_with_map, find_blocked_spawns, Appearance, Map, MapIndex, MapKind, SkillEffect, Skills, SummonTemplates};
+use crate::ui::{parse_preload_list, referenced_images, KeyBindings, SpriteSheets, DEFAULT_FONT_PATH, KEY_BINDINGS_PATH, PRELOAD_PATH, SPRITES_PATH};
+
+// Checks the data folder without opening a window, so a broken data change can fail a build
+pub fn run(data_root: Option<PathBuf>) -> Result<()> {
+    let data_root = data_root
+        .or_else(crate::data_directory)
+        .ok_or_else(|| anyhow!("No data folder given and CARGO_MANIFEST_DIR is not set"))?;
+    println!("Validating {}", data_root.display());
+
+    let mut validator = Validator::new(data_root);
+    validator.validate()?;
+
+    for asset in &validator.unreferenced {
+        println!("Unreferenced: {}", asset);
+    }
+    for warning in &validator.warnings {
+        println!("Warning: {}", warning);
+    }
+    for problem in &validator.problems {
+        println!("Error: {}", problem);
+    }
+    if validator.problems.is_empty() {
+        println!("Data is valid ({} unreferenced images)", validator.unreferenced.len());
+        Ok(())
+    } else {
+        Err(anyhow!("{} problem(s) found in data", validator.problems.len()))
+    }
+}
+
+struct Validator {
+    root: PathBuf,
+    problems: Vec<String>,
+    warnings: Vec<String>,
+    unreferenced: Vec<String>,
+}
+
+impl Validator {
+    fn new(root: PathBuf) -> Self {
+        Validator {
+            root,
+            problems: vec![],
+            warnings: vec![],
+            unreferenced: vec![],
+        }
+    }
+
+    // Paths in the data use the game's virtual filesystem, rooted at the data folder
+    fn resolve(&self, path: &str) -> PathBuf {
+        self.root.join(path.trim_start_matches('/'))
+    }
+
+    fn read_optional(&self, path: &str) -> Result<Option<String>> {
+        let full_path = self.resolve(path);
+        if full_path.is_file() {
+            Ok(Some(fs::read_to_string(full_path)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn validate(&mut self) -> Result<()> {
+        if !self.root.is_dir() {
+            return Err(anyhow!("Data folder {} does not exist", self.root.display()));
+        }
+
+        let sheets = match SpriteSheets::parse(self.read_optional(SPRITES_PATH)?.as_deref()) {
+            Ok(sheets) => sheets,
+            Err(e) => {
+                self.problems.push(format!("{} does not parse: {}", SPRITES_PATH, e));
+                SpriteSheets::parse(None)?
+            }
+        };
+        if let Some(text) = self.read_optional(KEY_BINDINGS_PATH)? {
+            if let Err(e) = KeyBindings::parse(&text) {
+                self.problems.push(format!("{} does not parse: {}", KEY_BINDINGS_PATH, e));
+            }
+        }
+        if !self.resolve(DEFAULT_FONT_PATH).is_file() {
+            self.problems.push(format!("Font {} is missing", DEFAULT_FONT_PATH));
+        }
+
+        let (index, maps) = self.validate_maps();
+        // Any valid map will do, as the world is only built to see what it refers to
+        let first_map = maps.into_iter().next().ok_or_else(|| anyhow!("No valid maps found under /maps"))?;
+
+        let preload = match self.read_optional(PRELOAD_PATH)?.map(|text| parse_preload_list(&text)) {
+            Some(Ok(preload)) => preload,
+            Some(Err(e)) => {
+                self.problems.push(format!("{} does not parse: {}", PRELOAD_PATH, e));
+                vec![]
+            }
+            None => vec![],
+        };
+
+        let mut world = create_game_world_with_map(first_map);
+        world.insert_resource(sheets);
+        world.insert_resource(index);
+        self.validate_definitions(&mut world);
+        self.validate_images(&mut world, &preload);
+        Ok(())
+    }
+
+    // Every map file in the data folder must deserialize, not just the one currently played
+    fn validate_maps(&mut self) -> (MapIndex, Vec<Map>) {
+        let mut valid_paths = vec![];
+        let mut maps = vec![];
+        for path in find_files(&self.root, "dat") {
+            let named = MapIndex::parse_path(&path);
+            // Misnamed maps are still loaded to check them, even though they are never played
+            let variant = named.map(|(_, variant)| variant).unwrap_or(Map::FIRST_VARIANT);
+            let map = match fs::File::open(self.resolve(&path))
+                .map_err(anyhow::Error::from)
+                .and_then(|file| Map::load(file, variant))
+            {
+                Ok(map) => map,
+                Err(e) => {
+                    self.problems.push(format!("Map {} does not load: {}", path, e));
+                    continue;
+                }
+            };
+            match named {
+                Some((kind, _)) if kind != map.kind => self
+                    .problems
+                    .push(format!("Map {} is in the {:?} folder but is a {:?} map", path, kind, map.kind)),
+                Some(_) => {
+                    let blocked = find_blocked_spawns(&mut create_game_world_with_map(map.clone())).len();
+                    if blocked > 0 {
+                        self.warnings
+                            .push(format!("Map {} has walls under {} starting character(s), so is never played", path, blocked));
+                    }
+                    maps.push(map);
+                    valid_paths.push(path);
+                }
+                None => self
+                    .warnings
+                    .push(format!("Map {} is not named /maps/<biome>/map<variant>.dat, so is never played", path)),
+            }
+        }
+
+        let index = MapIndex::from_paths(&valid_paths);
+        for kind in MapKind::ALL {
+            if index.variants(kind).is_empty() {
+                self.warnings.push(format!("No maps for {:?}", kind));
+            }
+        }
+        (index, maps)
+    }
+
+    fn validate_definitions(&mut self, world: &mut World) {
+        let mut characters = world.query::<(&Appearance, Option<&Skills>)>();
+        let templates = world.get_resource::<SummonTemplates>().unwrap();
+        let sheets = world.get_resource::<SpriteSheets>().unwrap();
+
+        let mut appearances: Vec<String> = vec![];
+        let mut skills = vec![];
+        for template in templates.iter() {
+            if sheets.get(&template.appearance).is_none() {
+                appearances.push(format!("Summon template {}", template.name));
+            }
+            skills.extend(template.skills.iter().cloned());
+        }
+        for (appearance, owner) in characters.iter(world) {
+            if sheets.get(&appearance.kind).is_none() {
+                appearances.push(appearance.kind.name().to_string());
+            }
+            if let Some(owner) = owner {
+                skills.extend(owner.skills.iter().cloned());
+            }
+        }
+        for appearance in appearances {
+            self.problems.push(format!("{} has no sprite sheet", appearance));
+        }
+
+        for skill in skills {
+            if let Some(projectile) = &skill.projectile {
+                if sheets.get(projectile).is_none() {
+                    self.problems
+                        .push(format!("Skill {} fires {}, which has no sprite sheet", skill.name, projectile.name()));
+                }
+            }
+            if let SkillEffect::Summon { template, .. } = &skill.kind {
+                if templates.get(template).is_none() {
+                    self.problems.push(format!("Skill {} summons unknown template {}", skill.name, template));
+                }
+            }
+        }
+    }
+
+    fn validate_images(&mut self, world: &mut World, preload: &[String]) {
+        let on_disk: HashSet<String> = find_files(&self.root, "png").into_iter().collect();
+        let mut referenced = HashSet::new();
+        for reference in referenced_images(world) {
+            match &reference.image {
+                Some(image) if on_disk.contains(image) => {
+                    referenced.insert(image.clone());
+                }
+                _ => self.problems.push(format!("Missing {}", reference.describe())),
+            }
+        }
+        for image in preload {
+            if on_disk.contains(image) {
+                referenced.insert(image.clone());
+            } else {
+                self.problems.push(format!("Missing preloaded image {}", image));
+            }
+        }
+
+        let mut unreferenced: Vec<String> = on_disk.difference(&referenced).cloned().collect();
+        unreferenced.sort();
+        self.unreferenced = unreferenced;
+    }
+}
+
+// Virtual paths, as the game would ask for them, of every file with the given extension
+fn find_files(root: &Path, extension: &str) -> Vec<String> {
+    let mut found = vec![];
+    find_files_in(root, root, extension, &mut found);
+    found.sort();
+    found
+}
+
+fn find_files_in(root: &Path, dir: &Path, extension: &str, found: &mut Vec<String>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            find_files_in(root, &path, extension, found);
+        } else if path
+            .extension()
+            .and_then(OsStr::to_str)
+            .map(|e| e.eq_ignore_ascii_case(extension))
+            .unwrap_or(false)
+        {
+            if let Ok(relative) = path.strip_prefix(root) {
+                found.push(format!("/{}", relative.to_string_lossy().replace('\\', "/")));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::Point;
+
+    use super::*;
+
+    // A scratch data folder, removed again once the test is done with it
+    struct TempData {
+        root: PathBuf,
+    }
+
+    impl TempData {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir().join(format!("arena-validate-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).unwrap();
+            TempData { root }
+        }
+
+        fn write(&self, path: &str, contents: &[u8]) {
+            let full_path = self.root.join(path.trim_start_matches('/'));
+            fs::create_dir_all(full_path.parent().unwrap()).unwrap();
+            fs::write(full_path, contents).unwrap();
+        }
+
+        fn write_map(&self, path: &str, kind: MapKind) {
+            self.write(path, &bincode::serialize(&Map::empty(kind)).unwrap());
+        }
+
+        fn write_walled_map(&self, path: &str, kind: MapKind, walls: &[Point]) {
+            let mut map = Map::empty(kind);
+            for wall in walls {
+                map.set_walkable(wall, false);
+            }
+            self.write(path, &bincode::serialize(&map).unwrap());
+        }
+    }
+
+    impl Drop for TempData {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    #[test]
+    fn find_files_by_extension() {
+        let data = TempData::new("find-files");
+        data.write("/images/a.png", b"");
+        data.write("/images/sub/B.PNG", b"");
+        data.write("/images/notes.txt", b"");
+        data.write("/maps/ashlands/map1.dat", b"");
+
+        assert_eq!(vec!["/images/a.png", "/images/sub/B.PNG"], find_files(&data.root, "png"));
+        assert_eq!(vec!["/maps/ashlands/map1.dat"], find_files(&data.root, "dat"));
+        assert!(find_files(&data.root.join("missing"), "png").is_empty());
+    }
+
+    #[test]
+    fn maps_must_match_their_folder() {
+        let data = TempData::new("maps");
+        data.write_map("/maps/ashlands/map1.dat", MapKind::Ashlands);
+        data.write_map("/maps/ashlands/map2.dat", MapKind::Ashlands);
+        data.write_map("/maps/beach/map1.dat", MapKind::Desert);
+        data.write_map("/maps/ashlands/extra.dat", MapKind::Ashlands);
+        data.write("/maps/winter/map1.dat", b"not a map");
+
+        let mut validator = Validator::new(data.root.clone());
+        let (index, maps) = validator.validate_maps();
+        assert_eq!(&[(MapKind::Ashlands, 1), (MapKind::Ashlands, 2)], index.maps());
+        assert_eq!(vec![1, 2], maps.iter().map(|m| m.variant).collect::<Vec<u32>>());
+
+        assert_eq!(2, validator.problems.len());
+        assert!(validator.problems[0].contains("/maps/beach/map1.dat is in the Beach folder but is a Desert map"));
+        assert!(validator.problems[1].contains("/maps/winter/map1.dat does not load"));
+        assert!(validator.warnings.iter().any(|w| w.contains("/maps/ashlands/extra.dat is not named")));
+        assert!(validator.warnings.iter().any(|w| w == "No maps for Beach"));
+        assert!(!validator.warnings.iter().any(|w| w == "No maps for Ashlands"));
+    }
+
+    #[test]
+    fn maps_must_leave_room_for_spawns() {
+        let data = TempData::new("spawns");
+        data.write_map("/maps/beach/map1.dat", MapKind::Beach);
+        data.write_walled_map("/maps/beach/map2.dat", MapKind::Beach, &[Point::new(8, 6), Point::new(4, 5)]);
+        data.write_walled_map("/maps/beach/map3.dat", MapKind::Beach, &[Point::new(0, 0)]);
+
+        let mut validator = Validator::new(data.root.clone());
+        validator.validate_maps();
+        let blocked: Vec<&String> = validator.warnings.iter().filter(|w| w.contains("starting character")).collect();
+        assert_eq!(
+            vec!["Map /maps/beach/map2.dat has walls under 2 starting character(s), so is never played"],
+            blocked
+        );
+    }
+
+    #[test]
+    fn reports_unreferenced_images() {
+        let data = TempData::new("images");
+        data.write("/maps/ashlands/map1.png", b"");
+        data.write("/images/battle/1_1.png", b"");
+        data.write("/images/preloaded.png", b"");
+        data.write("/images/unused.png", b"");
+
+        let mut world = World::new();
+        world.insert_resource(MapIndex::from_paths(&["/maps/ashlands/map1.dat"]));
+        world.insert_resource(SpriteSheets::parse(None).unwrap());
+        let mut validator = Validator::new(data.root.clone());
+        validator.validate_images(&mut world, &["/images/preloaded.png".to_string(), "/images/gone.png".to_string()]);
+
+        assert_eq!(vec!["/images/unused.png"], validator.unreferenced);
+        assert!(validator.problems.iter().any(|p| p == "Missing preloaded image /images/gone.png"));
+        assert!(validator.problems.iter().any(|p| p.contains("/ui/life_bar.png")));
+        assert!(!validator.problems.iter().any(|p| p.contains("/maps/ashlands/map1.png")));
+        assert!(!validator.problems.iter().any(|p| p.contains("/images/battle/1_1.png")));
+    }
+}